@@ -0,0 +1,121 @@
+use crate::{
+    config::{
+        AMPLITUDE_BREAKPOINTS, AMPLITUDE_DB_RANGE, AMPLITUDE_EXP_CURVATURE, AMPLITUDE_MAX_GAIN,
+        AMPLITUDE_MIN_GAIN, DEFAULT_AMPLITUDE_CURVE,
+    },
+    master_bus::db_to_linear,
+};
+
+/// curva de resposta do eixo X para o volume
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmplitudeCurve {
+    Linear,
+    Exponential,
+    Decibel,
+    SCurve,
+    /// pontos (entrada, saída) em [0, 1], interpolados linearmente
+    Breakpoints(&'static [(f32, f32)]),
+}
+
+impl AmplitudeCurve {
+    /// aplica a curva a `t` em [0, 1], retornando um valor em [0, 1]
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            AmplitudeCurve::Linear => t,
+            AmplitudeCurve::Exponential => {
+                let k = AMPLITUDE_EXP_CURVATURE;
+                ((k * t).exp() - 1.0) / (k.exp() - 1.0)
+            }
+            AmplitudeCurve::Decibel => {
+                // t = 0 corresponde a -AMPLITUDE_DB_RANGE dB, t = 1 a 0 dB
+                let floor = db_to_linear(-AMPLITUDE_DB_RANGE);
+                let gain = db_to_linear(-AMPLITUDE_DB_RANGE * (1.0 - t));
+                ((gain - floor) / (1.0 - floor)).clamp(0.0, 1.0)
+            }
+            AmplitudeCurve::SCurve => t * t * (3.0 - 2.0 * t),
+            AmplitudeCurve::Breakpoints(points) => interpolate_breakpoints(points, t),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AmplitudeCurve::Linear => "Linear",
+            AmplitudeCurve::Exponential => "Exp",
+            AmplitudeCurve::Decibel => "dB",
+            AmplitudeCurve::SCurve => "S",
+            AmplitudeCurve::Breakpoints(_) => "Pontos",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            AmplitudeCurve::Linear => AmplitudeCurve::Exponential,
+            AmplitudeCurve::Exponential => AmplitudeCurve::Decibel,
+            AmplitudeCurve::Decibel => AmplitudeCurve::SCurve,
+            AmplitudeCurve::SCurve => AmplitudeCurve::Breakpoints(AMPLITUDE_BREAKPOINTS),
+            AmplitudeCurve::Breakpoints(_) => AmplitudeCurve::Linear,
+        }
+    }
+}
+
+fn interpolate_breakpoints(points: &[(f32, f32)], t: f32) -> f32 {
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return t,
+    };
+
+    if t <= first.0 {
+        return first.1;
+    }
+    if t >= last.0 {
+        return last.1;
+    }
+
+    for pair in points.windows(2) {
+        let (x0, y0) = pair[0];
+        let (x1, y1) = pair[1];
+        if t >= x0 && t <= x1 {
+            if x1 - x0 <= f32::EPSILON {
+                return y1;
+            }
+            return y0 + (y1 - y0) * (t - x0) / (x1 - x0);
+        }
+    }
+
+    last.1
+}
+
+/// mapeamento de X em [-1, 1] para ganho em [min_gain, max_gain]
+#[derive(Debug, Clone, Copy)]
+pub struct AmplitudeMapping {
+    pub curve: AmplitudeCurve,
+    pub min_gain: f32,
+    pub max_gain: f32,
+}
+
+impl AmplitudeMapping {
+    pub fn new(curve: AmplitudeCurve, min_gain: f32, max_gain: f32) -> Self {
+        AmplitudeMapping {
+            curve,
+            min_gain,
+            max_gain,
+        }
+    }
+
+    pub fn map(&self, x: f32) -> f32 {
+        let t = (x + 1.0) / 2.0;
+        self.min_gain + (self.max_gain - self.min_gain) * self.curve.apply(t)
+    }
+}
+
+impl Default for AmplitudeMapping {
+    fn default() -> Self {
+        AmplitudeMapping::new(
+            DEFAULT_AMPLITUDE_CURVE,
+            AMPLITUDE_MIN_GAIN,
+            AMPLITUDE_MAX_GAIN,
+        )
+    }
+}
@@ -0,0 +1,230 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    config::{ARP_GATE, ARP_SYNC_SAMPLES},
+    rng::Rng,
+};
+
+/// suavização do ganho de articulação (~5 ms a 44.1 kHz), evita cliques
+const GATE_SMOOTHING: f32 = 0.005;
+
+/// ordem em que as notas do acorde são tocadas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArpPattern {
+    Up,
+    Down,
+    UpDown,
+    Random,
+}
+
+impl ArpPattern {
+    pub const ALL: &[ArpPattern] = &[
+        ArpPattern::Up,
+        ArpPattern::Down,
+        ArpPattern::UpDown,
+        ArpPattern::Random,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArpPattern::Up => "Sobe",
+            ArpPattern::Down => "Desce",
+            ArpPattern::UpDown => "Sobe-Desce",
+            ArpPattern::Random => "Aleatorio",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = ArpPattern::ALL
+            .iter()
+            .position(|pattern| pattern == self)
+            .unwrap_or(0);
+        ArpPattern::ALL[(index + 1) % ArpPattern::ALL.len()]
+    }
+
+    /// padrão correspondente a `t` em [0, 1], dividido em faixas iguais
+    pub fn from_position(t: f32) -> Self {
+        let index = (t.clamp(0.0, 1.0) * ArpPattern::ALL.len() as f32) as usize;
+        ArpPattern::ALL[index.min(ArpPattern::ALL.len() - 1)]
+    }
+}
+
+/// o que o eixo X controla no arpejador
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArpXControl {
+    Rate,
+    Pattern,
+}
+
+impl ArpXControl {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArpXControl::Rate => "Velocidade",
+            ArpXControl::Pattern => "Padrao",
+        }
+    }
+}
+
+struct ArpSettings {
+    enabled: bool,
+    notes: Vec<f32>,
+    pattern: ArpPattern,
+    rate: f32,
+    current_frequency: f32,
+}
+
+/// arpejador com relógio próprio, avançado a cada amostra pela thread de áudio
+pub struct Arpeggiator {
+    settings: Arc<Mutex<ArpSettings>>,
+    /// ligado e com notas, relido a cada ARP_SYNC_SAMPLES
+    active: bool,
+    sync_counter: usize,
+    notes: Vec<f32>,
+    pattern: ArpPattern,
+    step_samples: usize,
+    counter: usize,
+    step: usize,
+    frequency: f32,
+    gate_gain: f32,
+    rng: Rng,
+}
+
+impl Arpeggiator {
+    pub fn new() -> Self {
+        let settings = Arc::new(Mutex::new(ArpSettings {
+            enabled: false,
+            notes: Vec::new(),
+            pattern: ArpPattern::Up,
+            rate: 1.0,
+            current_frequency: 0.0,
+        }));
+        Self::with_settings(settings)
+    }
+
+    fn with_settings(settings: Arc<Mutex<ArpSettings>>) -> Self {
+        Arpeggiator {
+            settings,
+            active: false,
+            sync_counter: 0,
+            notes: Vec::new(),
+            pattern: ArpPattern::Up,
+            step_samples: 0,
+            counter: 0,
+            step: 0,
+            frequency: 0.0,
+            gate_gain: 0.0,
+            rng: Rng::new(),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        let mut settings = self.settings.lock().unwrap();
+        settings.enabled = enabled;
+    }
+
+    /// notas (em Hz, da mais grave para a mais aguda), padrão e notas por segundo
+    pub fn update(&self, notes: Vec<f32>, pattern: ArpPattern, rate: f32) {
+        let mut settings = self.settings.lock().unwrap();
+        settings.notes = notes;
+        settings.pattern = pattern;
+        settings.rate = rate.max(0.1);
+    }
+
+    pub fn get_pattern(&self) -> ArpPattern {
+        let settings = self.settings.lock().unwrap();
+        settings.pattern
+    }
+
+    pub fn get_rate(&self) -> f32 {
+        let settings = self.settings.lock().unwrap();
+        settings.rate
+    }
+
+    pub fn get_current_frequency(&self) -> f32 {
+        let settings = self.settings.lock().unwrap();
+        settings.current_frequency
+    }
+
+    /// frequência e ganho de articulação da amostra atual;
+    /// `None` se o arpejador estiver desligado
+    pub fn next(&mut self, sample_rate: u32) -> Option<(f32, f32)> {
+        if self.sync_counter == 0 {
+            self.sync();
+        }
+        self.sync_counter = (self.sync_counter + 1) % ARP_SYNC_SAMPLES;
+
+        if !self.active {
+            // ao religar, começa num passo novo
+            self.counter = 0;
+            return None;
+        }
+        if self.counter == 0 && !self.advance(sample_rate) {
+            return None;
+        }
+        self.counter = (self.counter + 1) % self.step_samples.max(1);
+
+        // abre a nota no início do passo e fecha após a fração ARP_GATE
+        let gate_open = (self.counter as f32) < self.step_samples as f32 * ARP_GATE;
+        let target = if gate_open { 1.0 } else { 0.0 };
+        self.gate_gain += (target - self.gate_gain) * GATE_SMOOTHING;
+
+        Some((self.frequency, self.gate_gain))
+    }
+
+    /// sem travar o mutex a cada amostra enquanto o arpejador está desligado
+    fn sync(&mut self) {
+        let settings = self.settings.lock().unwrap();
+        self.active = settings.enabled && !settings.notes.is_empty();
+    }
+
+    /// lê os ajustes e escolhe a próxima nota do padrão
+    fn advance(&mut self, sample_rate: u32) -> bool {
+        {
+            let settings = self.settings.lock().unwrap();
+            if !settings.enabled || settings.notes.is_empty() {
+                self.active = false;
+                return false;
+            }
+            self.notes.clone_from(&settings.notes);
+            self.pattern = settings.pattern;
+            self.step_samples = (sample_rate as f32 / settings.rate) as usize;
+        }
+
+        self.frequency = self.note_at(self.step);
+        self.step = self.step.wrapping_add(1);
+
+        let mut settings = self.settings.lock().unwrap();
+        settings.current_frequency = self.frequency;
+        true
+    }
+
+    fn note_at(&mut self, step: usize) -> f32 {
+        let count = self.notes.len();
+        let index = match self.pattern {
+            ArpPattern::Up => step % count,
+            ArpPattern::Down => count - 1 - step % count,
+            ArpPattern::UpDown => {
+                if count < 2 {
+                    0
+                } else {
+                    let cycle = 2 * (count - 1);
+                    let position = step % cycle;
+                    if position < count {
+                        position
+                    } else {
+                        cycle - position
+                    }
+                }
+            }
+            ArpPattern::Random => (self.rng.next_u32() as usize) % count,
+        };
+        self.notes[index]
+    }
+}
+
+impl Clone for Arpeggiator {
+    fn clone(&self) -> Self {
+        // relógio e posição no padrão não são compartilhados
+        Self::with_settings(self.settings.clone())
+    }
+}
@@ -153,13 +153,7 @@ impl ArucoProcessor {
     ) -> NormalizedPosition {
         for marker in markers {
             if marker.id == 0 {
-                let center = marker.center;
-
-                // normalizar posição para [-1, 1]
-                let x_normalized = ((center.x * 2.0) / frame_width as f32) - 1.0;
-                let y_normalized = ((center.y * 2.0) / frame_height as f32) - 1.0;
-
-                let position = NormalizedPosition::new(x_normalized, y_normalized, true);
+                let position = normalize_marker(marker, frame_width, frame_height);
                 self.last_position = position;
                 return position;
             }
@@ -170,7 +164,44 @@ impl ArucoProcessor {
         position
     }
 
+    /// posição, rotação e tamanho de cada marcador válido, junto com o seu id
+    pub fn calculate_marker_positions(
+        &self,
+        frame_width: i32,
+        frame_height: i32,
+        markers: &[DetectedMarker],
+    ) -> Vec<(i32, NormalizedPosition)> {
+        markers
+            .iter()
+            .map(|marker| {
+                (
+                    marker.id,
+                    normalize_marker(marker, frame_width, frame_height),
+                )
+            })
+            .collect()
+    }
+
     pub fn get_processed_frame(&self) -> &Mat {
         &self.processed_frame
     }
 }
+
+fn normalize_marker(
+    marker: &DetectedMarker,
+    frame_width: i32,
+    frame_height: i32,
+) -> NormalizedPosition {
+    let center = marker.center;
+
+    // normalizar posição para [-1, 1]
+    let x_normalized = ((center.x * 2.0) / frame_width as f32) - 1.0;
+    let y_normalized = ((center.y * 2.0) / frame_height as f32) - 1.0;
+
+    // tamanho como fração da altura do quadro
+    let size = marker.side_length() / frame_height as f32;
+
+    NormalizedPosition::new(x_normalized, y_normalized, true)
+        .with_rotation(marker.rotation())
+        .with_size(size)
+}
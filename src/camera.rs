@@ -8,29 +8,29 @@ use std::error::Error;
 use crate::config::VIDEO_PATHS;
 
 pub fn initialize_capture() -> Result<(VideoCapture, bool), Box<dyn Error>> {
-    println!("[INFO] Tentando abrir a câmera...");
+    log!("[INFO] Tentando abrir a câmera...");
     let mut cam = VideoCapture::new(0, CAP_ANY)?;
     
     if cam.is_opened()? {
-        println!("[START] Câmera iniciada com sucesso");
+        log!("[START] Câmera iniciada com sucesso");
         return Ok((cam, true));
     }
     
     // fallback para vídeo
-    println!("[INFO] Câmera não encontrada. Carregando vídeo de exemplo...");
+    log!("[INFO] Câmera não encontrada. Carregando vídeo de exemplo...");
     
     for video_path in VIDEO_PATHS {
-        println!("[INFO] Tentando abrir: {}", video_path);
+        log!("[INFO] Tentando abrir: {}", video_path);
         cam = VideoCapture::from_file(video_path, CAP_ANY)?;
         if cam.is_opened()? {
-            println!("[START] Vídeo carregado: {}", video_path);
+            log!("[START] Vídeo carregado: {}", video_path);
             return Ok((cam, false));
         }
     }
     
-    println!("[ERROR] Não foi possível abrir câmera nem vídeo!");
-    println!("[TIP] Coloque um arquivo de vídeo (ex: video.mp4) na pasta do projeto.");
-    println!("[TIP] Ou conecte uma webcam e verifique as permissões.");
+    log!("[ERROR] Não foi possível abrir câmera nem vídeo!");
+    log!("[TIP] Coloque um arquivo de vídeo (ex: video.mp4) na pasta do projeto.");
+    log!("[TIP] Ou conecte uma webcam e verifique as permissões.");
     
     Err("Nenhuma fonte de vídeo disponível".into())
 }
@@ -45,12 +45,12 @@ pub fn read_frame(
     if frame.empty() {
         if !is_camera {
             // Reiniciar vídeo
-            println!("[INFO] Fim do vídeo. Reiniciando...");
+            log!("[INFO] Fim do vídeo. Reiniciando...");
             cam.set(CAP_PROP_POS_FRAMES, 0.0)?;
             cam.read(frame)?;
             
             if frame.empty() {
-                println!("[ERROR] Não foi possível reiniciar o vídeo.");
+                log!("[ERROR] Não foi possível reiniciar o vídeo.");
                 return Ok(false);
             }
         }
@@ -61,6 +61,6 @@ pub fn read_frame(
 
 pub fn release_capture(cam: &mut VideoCapture) -> Result<(), Box<dyn Error>> {
     cam.release()?;
-    println!("[INFO] Recurso da câmera liberado");
+    log!("[INFO] Recurso da câmera liberado");
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,183 @@
+use std::f32::consts::PI;
+
+use crate::pitch::{PitchMapping, Scale};
+
+pub const MAX_CHORD_NOTES: usize = 4;
+
+const ROMAN_DEGREES: &[&str] = &["I", "II", "III", "IV", "V", "VI", "VII"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChordType {
+    Triad,
+    Seventh,
+}
+
+impl ChordType {
+    pub fn note_count(&self) -> usize {
+        match self {
+            ChordType::Triad => 3,
+            ChordType::Seventh => 4,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChordType::Triad => "Triade",
+            ChordType::Seventh => "Tetrade",
+        }
+    }
+}
+
+/// disposição das notas do acorde
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChordVoicing {
+    Inversion(usize),
+    /// posição aberta: notas alternadas sobem uma oitava
+    Spread,
+}
+
+impl ChordVoicing {
+    /// `t` em [0, 1]: as inversões em ordem e, na última faixa, a posição aberta
+    pub fn from_position(t: f32, chord_type: ChordType) -> Self {
+        let options = chord_type.note_count() + 1;
+        let index = ((t.clamp(0.0, 1.0) * options as f32) as usize).min(options - 1);
+
+        if index == options - 1 {
+            ChordVoicing::Spread
+        } else {
+            ChordVoicing::Inversion(index)
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChordVoicing::Inversion(0) => "Fundamental",
+            ChordVoicing::Inversion(1) => "1a inv.",
+            ChordVoicing::Inversion(2) => "2a inv.",
+            ChordVoicing::Inversion(_) => "3a inv.",
+            ChordVoicing::Spread => "Aberto",
+        }
+    }
+}
+
+/// escala em que os acordes são montados: terças empilhadas numa pentatônica dariam
+/// quartas e inversões, então as pentatônicas usam a escala de sete notas que as contém
+fn chord_scale(mapping: &PitchMapping) -> Scale {
+    mapping.scale().heptatonic()
+}
+
+/// grau do acorde para Y em [-1, 1], contado na escala dos acordes
+pub fn degree_for_position(mapping: &PitchMapping, y: f32) -> usize {
+    let degrees = chord_scale(mapping).intervals().len() * mapping.octaves() as usize;
+    let t = ((y + 1.0) / 2.0).clamp(0.0, 1.0);
+    ((t * degrees as f32) as usize).min(degrees - 1)
+}
+
+/// algarismo romano do grau dentro da escala dos acordes
+pub fn degree_name(mapping: &PitchMapping, degree: usize) -> &'static str {
+    let scale_degree = degree % chord_scale(mapping).intervals().len();
+    ROMAN_DEGREES.get(scale_degree).copied().unwrap_or("?")
+}
+
+/// empilha terças da escala sobre o grau (notas MIDI já transpostas, em ordem crescente)
+pub fn build_chord(
+    mapping: &PitchMapping,
+    degree: usize,
+    chord_type: ChordType,
+    voicing: ChordVoicing,
+) -> Vec<i32> {
+    let scale = chord_scale(mapping);
+    let mut notes: Vec<i32> = (0..chord_type.note_count())
+        .map(|index| mapping.degree_to_note_in(scale, degree + 2 * index) + mapping.transpose())
+        .collect();
+
+    match voicing {
+        ChordVoicing::Inversion(count) => {
+            for note in notes.iter_mut().take(count) {
+                *note += 12;
+            }
+        }
+        ChordVoicing::Spread => {
+            for note in notes.iter_mut().skip(1).step_by(2) {
+                *note += 12;
+            }
+        }
+    }
+
+    notes.sort();
+    notes
+}
+
+/// banco de osciladores senoidais, um por nota do acorde
+#[derive(Clone)]
+pub struct ChordVoice {
+    phases: [f32; MAX_CHORD_NOTES],
+}
+
+impl ChordVoice {
+    pub fn new() -> Self {
+        ChordVoice {
+            phases: [0.0; MAX_CHORD_NOTES],
+        }
+    }
+
+    pub fn next_sample(&mut self, frequencies: &[f32], sample_rate: u32) -> f32 {
+        if frequencies.is_empty() {
+            return 0.0;
+        }
+
+        let mut output = 0.0;
+        for (phase, frequency) in self.phases.iter_mut().zip(frequencies) {
+            *phase += 2.0 * PI * frequency / sample_rate as f32;
+            if *phase > 2.0 * PI {
+                *phase -= 2.0 * PI;
+            }
+            output += phase.sin();
+        }
+
+        // mantém o volume percebido parecido com o de uma nota só
+        output / (frequencies.len() as f32).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pentatonic_mapping() -> PitchMapping {
+        PitchMapping::new(48, 2, 440.0, 0, Scale::PentatonicMajor)
+    }
+
+    #[test]
+    fn pentatonic_scale_builds_major_scale_triads() {
+        let mapping = pentatonic_mapping();
+        let triad = |degree| {
+            build_chord(
+                &mapping,
+                degree,
+                ChordType::Triad,
+                ChordVoicing::Inversion(0),
+            )
+        };
+
+        assert_eq!(triad(0), vec![48, 52, 55]); // C E G
+        assert_eq!(triad(1), vec![50, 53, 57]); // D F A
+        assert_eq!(triad(4), vec![55, 59, 62]); // G B D
+        assert_eq!(triad(5), vec![57, 60, 64]); // A C E
+    }
+
+    #[test]
+    fn seventh_chord_stacks_four_thirds() {
+        let mapping = pentatonic_mapping();
+        let chord = build_chord(&mapping, 4, ChordType::Seventh, ChordVoicing::Inversion(0));
+        assert_eq!(chord, vec![55, 59, 62, 65]); // G7
+    }
+
+    #[test]
+    fn degrees_follow_the_seven_note_scale() {
+        let mapping = pentatonic_mapping();
+        assert_eq!(degree_for_position(&mapping, 1.0), 13);
+        assert_eq!(degree_name(&mapping, 6), "VII");
+        assert_eq!(degree_name(&mapping, 7), "I");
+    }
+}
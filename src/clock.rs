@@ -0,0 +1,169 @@
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex};
+
+use crate::config::{
+    CLICK_ACCENT_FREQUENCY, CLICK_FREQUENCY, CLICK_LEVEL, CLICK_MS, CLOCK_BEATS_PER_BAR, CLOCK_BPM,
+    CLOCK_MAX_BPM, CLOCK_MIN_BPM, CLOCK_SUBDIVISION, CLOCK_SYNC_SAMPLES,
+};
+
+/// estado do relógio visto pela interface
+#[derive(Debug, Clone, Copy)]
+pub struct ClockStatus {
+    pub bpm: f32,
+    pub beat: u64,
+    /// posição dentro do tempo atual, em [0, 1)
+    pub beat_phase: f32,
+    pub click_enabled: bool,
+    pub quantize_enabled: bool,
+}
+
+/// o que aconteceu no relógio nesta amostra
+#[derive(Debug, Clone, Copy)]
+pub struct ClockTick {
+    /// início de uma subdivisão do tempo (onde as notas quantizadas mudam)
+    pub subdivision: bool,
+    pub click: f32,
+}
+
+struct ClockShared {
+    status: ClockStatus,
+}
+
+/// relógio de BPM da thread de áudio, com metrônomo opcional
+pub struct TempoClock {
+    shared: Arc<Mutex<ClockShared>>,
+    samples_per_second: f64,
+    bpm: f32,
+    click_enabled: bool,
+    quantize_enabled: bool,
+    beat_position: f64,
+    click_remaining: usize,
+    click_frequency: f32,
+    click_phase: f32,
+    sync_counter: usize,
+}
+
+impl TempoClock {
+    pub fn new(sample_rate: u32) -> Self {
+        let shared = Arc::new(Mutex::new(ClockShared {
+            status: ClockStatus {
+                bpm: CLOCK_BPM,
+                beat: 0,
+                beat_phase: 0.0,
+                click_enabled: false,
+                quantize_enabled: false,
+            },
+        }));
+        Self::with_shared(shared, sample_rate as f64)
+    }
+
+    fn with_shared(shared: Arc<Mutex<ClockShared>>, samples_per_second: f64) -> Self {
+        let status = shared.lock().unwrap().status;
+
+        TempoClock {
+            shared,
+            samples_per_second,
+            bpm: status.bpm,
+            click_enabled: status.click_enabled,
+            quantize_enabled: status.quantize_enabled,
+            beat_position: 0.0,
+            click_remaining: 0,
+            click_frequency: CLICK_FREQUENCY,
+            click_phase: 0.0,
+            sync_counter: 0,
+        }
+    }
+
+    pub fn set_bpm(&self, bpm: f32) {
+        // NaN passaria pelo clamp e travaria o relógio
+        if !bpm.is_finite() {
+            return;
+        }
+        let mut shared = self.shared.lock().unwrap();
+        shared.status.bpm = bpm.clamp(CLOCK_MIN_BPM, CLOCK_MAX_BPM);
+    }
+
+    pub fn set_click_enabled(&self, enabled: bool) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.status.click_enabled = enabled;
+    }
+
+    pub fn set_quantize_enabled(&self, enabled: bool) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.status.quantize_enabled = enabled;
+    }
+
+    pub fn status(&self) -> ClockStatus {
+        let shared = self.shared.lock().unwrap();
+        shared.status
+    }
+
+    pub fn is_quantize_enabled(&self) -> bool {
+        self.quantize_enabled
+    }
+
+    /// avança uma amostra
+    pub fn tick(&mut self) -> ClockTick {
+        if self.sync_counter == 0 {
+            self.sync();
+        }
+        self.sync_counter = (self.sync_counter + 1) % CLOCK_SYNC_SAMPLES;
+
+        let previous = self.beat_position;
+        self.beat_position += self.bpm as f64 / 60.0 / self.samples_per_second;
+
+        let subdivisions = CLOCK_SUBDIVISION as f64;
+        let subdivision =
+            (self.beat_position * subdivisions).floor() > (previous * subdivisions).floor();
+
+        if self.beat_position.floor() > previous.floor() {
+            let beat = self.beat_position.floor() as u64;
+            self.click_frequency = if beat.is_multiple_of(CLOCK_BEATS_PER_BAR as u64) {
+                CLICK_ACCENT_FREQUENCY
+            } else {
+                CLICK_FREQUENCY
+            };
+            self.click_remaining = (CLICK_MS as f64 / 1000.0 * self.samples_per_second) as usize;
+            self.click_phase = 0.0;
+        }
+
+        ClockTick {
+            subdivision,
+            click: self.next_click(),
+        }
+    }
+
+    fn next_click(&mut self) -> f32 {
+        if self.click_remaining == 0 || !self.click_enabled {
+            self.click_remaining = self.click_remaining.saturating_sub(1);
+            return 0.0;
+        }
+
+        let total = CLICK_MS / 1000.0 * self.samples_per_second as f32;
+        let envelope = self.click_remaining as f32 / total;
+        self.click_remaining -= 1;
+
+        self.click_phase += 2.0 * PI * self.click_frequency / self.samples_per_second as f32;
+        if self.click_phase > 2.0 * PI {
+            self.click_phase -= 2.0 * PI;
+        }
+
+        self.click_phase.sin() * envelope * envelope * CLICK_LEVEL
+    }
+
+    fn sync(&mut self) {
+        let mut shared = self.shared.lock().unwrap();
+        self.bpm = shared.status.bpm;
+        self.click_enabled = shared.status.click_enabled;
+        self.quantize_enabled = shared.status.quantize_enabled;
+        shared.status.beat = self.beat_position.floor() as u64;
+        shared.status.beat_phase = self.beat_position.fract() as f32;
+    }
+}
+
+impl Clone for TempoClock {
+    fn clone(&self) -> Self {
+        // cada instância conta o próprio tempo; só a que toca publica o estado
+        Self::with_shared(self.shared.clone(), self.samples_per_second)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{
+    debug::DebugManager,
+    formant::FormantAxis,
+    harmonizer::HarmonyInterval,
+    looper::LooperCommand,
+    pitch::Scale,
+    theremin::{ThereminController, VoiceType},
+};
+
+/// ação sobre o instrumento, vinda do teclado ou de um controle remoto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Quit,
+    ToggleSound,
+    SetSound(bool),
+    ToggleDebug,
+    ToggleEventStream,
+    ToggleCompressor,
+    SetCompressor(bool),
+    CycleAmplitudeCurve,
+    CycleVoice,
+    SetVoice(VoiceType),
+    ToggleGranularY,
+    ToggleNoiseX,
+    CycleHarmony,
+    SetHarmony(HarmonyInterval),
+    CycleFormant,
+    SetFormant(FormantAxis),
+    CyclePlayMode,
+    CycleScale,
+    SetScale(Scale),
+    ToggleContinuousPitch,
+    SetContinuousPitch(bool),
+    ToggleChordType,
+    Looper(LooperCommand),
+    ExportMidi,
+    ToggleLiveMidi,
+    ToggleMpe,
+    CycleLossPolicy,
+    /// passos de CLOCK_BPM_STEP
+    ChangeTempo(i32),
+    SetTempo(f32),
+    ToggleClick,
+    ToggleQuantize,
+    ToggleArpX,
+    CycleArpPattern,
+    Transpose(i32),
+    SetTranspose(i32),
+}
+
+impl Command {
+    /// comando ligado a uma tecla do wait_key
+    pub fn from_key(key: i32) -> Option<Command> {
+        let command = match key {
+            27 => Command::Quit,                                    // ESC
+            32 => Command::ToggleSound,                             // ESPAÇO
+            43 | 61 => Command::Transpose(1),                       // '+' ou '='
+            45 | 95 => Command::Transpose(-1),                      // '-' ou '_'
+            44 => Command::ChangeTempo(-1),                         // ','
+            46 => Command::ChangeTempo(1),                          // '.'
+            65 | 97 => Command::CycleAmplitudeCurve,                // 'A'
+            66 | 98 => Command::ToggleClick,                        // 'B'
+            67 | 99 => Command::ToggleCompressor,                   // 'C'
+            68 | 100 => Command::ExportMidi,                        // 'D'
+            69 | 101 => Command::ToggleContinuousPitch,             // 'E'
+            70 | 102 => Command::CycleFormant,                      // 'F'
+            71 | 103 => Command::ToggleGranularY,                   // 'G'
+            72 | 104 => Command::CycleLossPolicy,                   // 'H'
+            73 | 105 => Command::CycleHarmony,                      // 'I'
+            74 | 106 => Command::ToggleLiveMidi,                    // 'J'
+            75 | 107 => Command::Looper(LooperCommand::Clear),      // 'K'
+            76 | 108 => Command::Looper(LooperCommand::TogglePlay), // 'L'
+            77 | 109 => Command::CyclePlayMode,                     // 'M'
+            78 | 110 => Command::ToggleNoiseX,                      // 'N'
+            79 | 111 => Command::Looper(LooperCommand::Overdub),    // 'O'
+            80 | 112 => Command::CycleArpPattern,                   // 'P'
+            81 | 113 => Command::ToggleQuantize,                    // 'Q'
+            82 | 114 => Command::Looper(LooperCommand::Record),     // 'R'
+            83 | 115 => Command::CycleScale,                        // 'S'
+            84 | 116 => Command::ToggleChordType,                   // 'T'
+            85 | 117 => Command::ToggleMpe,                         // 'U'
+            86 | 118 => Command::ToggleDebug,                       // 'V'
+            87 | 119 => Command::CycleVoice,                        // 'W'
+            88 | 120 => Command::ToggleArpX,                        // 'X'
+            89 | 121 => Command::ToggleEventStream,                 // 'Y'
+            _ => return None,
+        };
+        Some(command)
+    }
+}
+
+/// escala pelo nome mostrado na interface, sem diferenciar maiúsculas
+pub fn scale_by_name(name: &str) -> Option<Scale> {
+    Scale::ALL
+        .iter()
+        .copied()
+        .find(|scale| scale.name().eq_ignore_ascii_case(name))
+}
+
+/// voz pelo nome mostrado na interface, sem diferenciar maiúsculas
+pub fn voice_by_name(name: &str) -> Option<VoiceType> {
+    VoiceType::ALL
+        .iter()
+        .copied()
+        .find(|voice| voice.name().eq_ignore_ascii_case(name))
+}
+
+/// executa o comando; devolve false quando o programa deve encerrar
+pub fn execute(
+    command: Command,
+    controller: &mut ThereminController,
+    debug_manager: &mut DebugManager,
+) -> bool {
+    match command {
+        Command::Quit => {
+            log!("[INFO] Saindo...");
+            controller.stop();
+            return false;
+        }
+        Command::ToggleSound => controller.toggle_sound(),
+        Command::SetSound(enabled) => controller.set_sound(enabled),
+        Command::ToggleDebug => {
+            if debug_manager.toggle_debug_mode() {
+                log!("[DEBUG] Modo debug ATIVADO");
+            } else {
+                log!("[DEBUG] Modo debug DESATIVADO");
+            }
+        }
+        Command::ToggleEventStream => debug_manager.toggle_event_stream(),
+        Command::ToggleCompressor => controller.toggle_compressor(),
+        Command::SetCompressor(enabled) => controller.set_compressor(enabled),
+        Command::CycleAmplitudeCurve => controller.cycle_amplitude_curve(),
+        Command::CycleVoice => controller.cycle_voice(),
+        Command::SetVoice(voice) => controller.set_voice(voice),
+        Command::ToggleGranularY => controller.toggle_granular_y_control(),
+        Command::ToggleNoiseX => controller.toggle_noise_x_control(),
+        Command::CycleHarmony => controller.cycle_harmony_interval(),
+        Command::SetHarmony(interval) => controller.set_harmony_interval(interval),
+        Command::CycleFormant => controller.cycle_formant_axis(),
+        Command::SetFormant(axis) => controller.set_formant_axis(axis),
+        Command::CyclePlayMode => controller.cycle_play_mode(),
+        Command::CycleScale => controller.cycle_scale(),
+        Command::SetScale(scale) => controller.set_scale(scale),
+        Command::ToggleContinuousPitch => controller.toggle_continuous_pitch(),
+        Command::SetContinuousPitch(continuous) => controller.set_continuous_pitch(continuous),
+        Command::ToggleChordType => controller.toggle_chord_type(),
+        Command::Looper(looper_command) => controller.looper_command(looper_command),
+        Command::ExportMidi => controller.export_midi(),
+        Command::ToggleLiveMidi => controller.toggle_live_midi(),
+        Command::ToggleMpe => controller.toggle_mpe(),
+        Command::CycleLossPolicy => controller.cycle_loss_policy(),
+        Command::ChangeTempo(steps) => controller.change_tempo(steps),
+        Command::SetTempo(bpm) => controller.set_tempo(bpm),
+        Command::ToggleClick => controller.toggle_click(),
+        Command::ToggleQuantize => controller.toggle_quantize(),
+        Command::ToggleArpX => controller.toggle_arp_x_control(),
+        Command::CycleArpPattern => controller.cycle_arp_pattern(),
+        Command::Transpose(semitones) => controller.transpose_by(semitones),
+        Command::SetTranspose(transpose) => controller.set_transpose(transpose),
+    }
+    true
+}
@@ -1,5 +1,10 @@
 use opencv::{core::Scalar, objdetect::PredefinedDictionaryType};
 
+use crate::{
+    amplitude::AmplitudeCurve, events::EventTarget, marker_loss::MarkerLossPolicy,
+    midi_in::CcTarget, midi_out::MidiBackend, pitch::Scale,
+};
+
 /// fallback video paths
 pub const VIDEO_PATHS: &[&str] = &[
     "video.mp4",
@@ -18,3 +23,175 @@ pub const COLOR_RED: Scalar = Scalar::new(0.0, 0.0, 255.0, 0.0);
 pub const COLOR_WHITE: Scalar = Scalar::new(255.0, 255.0, 255.0, 0.0);
 pub const COLOR_BLUE: Scalar = Scalar::new(255.0, 0.0, 0.0, 0.0);
 pub const COLOR_YELLOW: Scalar = Scalar::new(0.0, 255.0, 255.0, 0.0);
+
+// master bus
+pub const MASTER_CEILING: f32 = 0.8;
+pub const LIMITER_LOOKAHEAD_MS: f32 = 5.0;
+pub const LIMITER_RELEASE_MS: f32 = 80.0;
+pub const COMPRESSOR_THRESHOLD_DB: f32 = -12.0;
+pub const COMPRESSOR_RATIO: f32 = 4.0;
+pub const COMPRESSOR_ATTACK_MS: f32 = 10.0;
+pub const COMPRESSOR_RELEASE_MS: f32 = 120.0;
+pub const COMPRESSOR_MAKEUP_DB: f32 = 3.0;
+pub const METER_BLOCK_SIZE: usize = 1024;
+pub const METER_PEAK_DECAY: f32 = 0.9;
+
+// resposta de amplitude (eixo X)
+pub const DEFAULT_AMPLITUDE_CURVE: AmplitudeCurve = AmplitudeCurve::Decibel;
+pub const AMPLITUDE_MIN_GAIN: f32 = 0.05;
+pub const AMPLITUDE_MAX_GAIN: f32 = 1.0;
+pub const AMPLITUDE_DB_RANGE: f32 = 40.0;
+pub const AMPLITUDE_EXP_CURVATURE: f32 = 4.0;
+pub const AMPLITUDE_BREAKPOINTS: &[(f32, f32)] = &[(0.0, 0.0), (0.3, 0.1), (0.7, 0.5), (1.0, 1.0)];
+
+// afinação e extensão (eixo Y)
+pub const PITCH_LOWEST_NOTE: i32 = 48; // C3 (MIDI)
+pub const PITCH_OCTAVES: u32 = 2;
+pub const PITCH_REFERENCE_A4: f32 = 440.0;
+pub const PITCH_TRANSPOSE: i32 = 0;
+pub const PITCH_TRANSPOSE_LIMIT: i32 = 24;
+pub const PITCH_SCALE: Scale = Scale::PentatonicMajor;
+
+// sampler: (arquivo, nota raiz MIDI, nota mais grave, nota mais aguda)
+pub const SAMPLER_ZONES: &[(&str, i32, i32, i32)] = &[
+    ("samples/sampler_c3.wav", 48, 0, 54),
+    ("samples/sampler_c4.wav", 60, 55, 127),
+];
+pub const SAMPLER_LOOP_START: f32 = 0.25; // fração do arquivo
+pub const SAMPLER_LOOP_END: f32 = 0.9;
+pub const SAMPLER_CROSSFADE_MS: f32 = 40.0;
+
+// granular
+pub const GRANULAR_FILE: &str = "samples/granular.wav";
+pub const GRANULAR_ROOT_NOTE: i32 = 60; // altura original do arquivo (C4)
+pub const GRANULAR_GRAIN_MS: f32 = 80.0;
+pub const GRANULAR_SPRAY_MS: f32 = 20.0;
+pub const GRANULAR_DENSITY: f32 = 25.0; // grãos por segundo
+pub const GRANULAR_MIN_DENSITY: f32 = 4.0;
+pub const GRANULAR_MAX_DENSITY: f32 = 80.0;
+pub const GRANULAR_MAX_GRAINS: usize = 32;
+pub const GRANULAR_GAIN: f32 = 0.7;
+pub const GRANULAR_SYNC_SAMPLES: usize = 256; // amostras entre leituras do eixo Y
+
+// pads: (nome, esquerda, topo, direita, base, arquivo), coordenadas normalizadas [-1, 1]
+pub const DRUM_ZONES: &[(&str, f32, f32, f32, f32, &str)] = &[
+    ("Chimbal", -0.95, -0.95, -0.05, -0.05, "samples/hat.wav"),
+    ("Prato", 0.05, -0.95, 0.95, -0.05, "samples/crash.wav"),
+    ("Bumbo", -0.95, 0.05, -0.05, 0.95, "samples/kick.wav"),
+    ("Caixa", 0.05, 0.05, 0.95, 0.95, "samples/snare.wav"),
+];
+pub const DRUM_HYSTERESIS: f32 = 0.05;
+pub const DRUM_MAX_VOICES: usize = 8;
+pub const DRUM_GAIN: f32 = 0.8;
+
+// arpejador
+pub const ARP_INTERVALS: &[i32] = &[0, 4, 7, 12]; // semitons a partir da fundamental
+pub const ARP_MIN_RATE: f32 = 2.0; // notas por segundo
+pub const ARP_MAX_RATE: f32 = 16.0;
+pub const ARP_DEFAULT_RATE: f32 = 6.0;
+pub const ARP_GATE: f32 = 0.8; // fração do passo em que a nota soa
+pub const ARP_AMPLITUDE: f32 = 0.7;
+pub const ARP_SYNC_SAMPLES: usize = 64; // amostras entre leituras do liga/desliga
+
+// modo acorde
+pub const CHORD_AMPLITUDE: f32 = 0.7;
+
+// looper
+pub const LOOPER_MAX_SECONDS: f32 = 8.0;
+pub const LOOPER_LEVEL: f32 = 0.8;
+pub const LOOPER_OVERDUB_FEEDBACK: f32 = 0.9;
+pub const LOOPER_SYNC_SAMPLES: usize = 256;
+
+// perda do marcador de controle
+pub const MARKER_LOSS_POLICY: MarkerLossPolicy = MarkerLossPolicy::Hold;
+pub const MARKER_LOSS_FADE_MS: f32 = 500.0;
+pub const MARKER_LOSS_TIMEOUT_MS: f32 = 1000.0;
+
+// afinador
+pub const TUNER_RANGE_CENTS: f32 = 50.0;
+pub const TUNER_IN_TUNE_CENTS: f32 = 5.0;
+
+// MIDI
+pub const MIDI_CHANNEL: u8 = 0;
+pub const MIDI_AMPLITUDE_CC: u8 = 11; // 7 (volume) ou 11 (expressão)
+pub const MIDI_PITCH_BEND_RANGE: u8 = 2; // semitons
+pub const MIDI_TICKS_PER_QUARTER: u16 = 480;
+pub const MIDI_EXPORT_PREFIX: &str = "performance";
+pub const MIDI_OUTPUT_BACKEND: MidiBackend = MidiBackend::Alsa;
+pub const MIDI_PORT_NAME: &str = "ArUco Theremin";
+pub const MPE_MEMBER_CHANNELS: u8 = 15;
+pub const MPE_PITCH_BEND_RANGE: u8 = 48; // semitons, padrão do MPE
+pub const MPE_SLIDE_CC: u8 = 74;
+pub const MPE_MAX_MARKER_SIZE: f32 = 0.4; // lado (fração da altura) para pressão máxima
+pub const MIDI_INPUT_ENABLED: bool = true;
+pub const MIDI_INPUT_BACKEND: MidiBackend = MidiBackend::Alsa;
+pub const MIDI_INPUT_PORT_NAME: &str = "ArUco Theremin entrada";
+pub const MIDI_INPUT_CC_MAP: &[(u8, CcTarget)] = &[
+    (20, CcTarget::Tempo),
+    (21, CcTarget::Compressor),
+    (22, CcTarget::Harmony),
+    (23, CcTarget::Formant),
+];
+
+// OSC
+pub const OSC_OUTPUT_ENABLED: bool = true;
+pub const OSC_TARGET: &str = "127.0.0.1:9000";
+pub const OSC_INPUT_ENABLED: bool = true;
+// só esta máquina; "0.0.0.0:9001" aceita comandos de qualquer aparelho da rede, sem senha
+pub const OSC_LISTEN_ADDRESS: &str = "127.0.0.1:9001";
+
+// WebSocket (só localhost por padrão)
+pub const WEBSOCKET_ENABLED: bool = true;
+pub const WEBSOCKET_ADDRESS: &str = "127.0.0.1:8765";
+pub const WEBSOCKET_MAX_CLIENTS: usize = 8;
+pub const WEBSOCKET_MAX_BUFFER: usize = 1 << 20; // bytes pendentes por cliente
+// páginas que podem conectar (qualquer porta); clientes sem Origin, fora do navegador,
+// são sempre aceitos
+pub const WEBSOCKET_ALLOWED_ORIGINS: &[&str] = &["http://localhost", "http://127.0.0.1"];
+
+// socket de controle (só em sistemas Unix)
+#[cfg(unix)]
+pub const CONTROL_SOCKET_ENABLED: bool = true;
+#[cfg(unix)]
+pub const CONTROL_SOCKET_PATH: &str = "/tmp/aruco-theremin.sock";
+#[cfg(unix)]
+pub const CONTROL_MAX_LINE: usize = 4096; // bytes sem quebra de linha antes de derrubar o cliente
+
+// eventos JSON lines
+pub const EVENT_STREAM_ENABLED: bool = false;
+pub const EVENT_STREAM_TARGET: EventTarget = EventTarget::File; // Stdout para ler pelo pipe
+pub const EVENT_STREAM_PATH: &str = "theremin_events.jsonl"; // recriado a cada abertura
+
+// harmonizador
+pub const HARMONY_LEVEL: f32 = 0.6; // relativo à voz principal
+pub const HARMONY_DETUNE_CENTS: f32 = 6.0;
+
+// filtro de formantes
+pub const FORMANT_GAIN: f32 = 2.5; // compensa a energia removida pelos passa-bandas
+pub const FORMANT_ROTATION_RANGE: f32 = 90.0; // graus para cada lado, de "a" a "u"
+pub const FORMANT_UPDATE_SAMPLES: usize = 32;
+
+// corda dedilhada (Karplus-Strong)
+pub const STRING_DAMPING: f32 = 0.996;
+pub const STRING_BRIGHTNESS: f32 = 0.5; // 0 abafada, 1 brilhante
+pub const STRING_MAX_SPEED: f32 = 4.0; // unidades normalizadas por segundo para força máxima
+pub const STRING_MIN_PLUCK: f32 = 0.2;
+pub const STRING_SYNC_SAMPLES: usize = 64; // amostras entre leituras das palhetadas
+
+// vozes de ruído
+pub const NOISE_GAIN: f32 = 0.6;
+pub const NOISE_MIX: f32 = 0.5; // mistura oscilador/ruído quando o X controla a cor
+pub const NOISE_SYNC_SAMPLES: usize = 256; // amostras entre leituras do eixo X
+
+// relógio e metrônomo
+pub const CLOCK_BPM: f32 = 120.0;
+pub const CLOCK_MIN_BPM: f32 = 40.0;
+pub const CLOCK_MAX_BPM: f32 = 240.0;
+pub const CLOCK_BPM_STEP: f32 = 5.0;
+pub const CLOCK_BEATS_PER_BAR: u32 = 4;
+pub const CLOCK_SUBDIVISION: u32 = 2; // divisões por tempo usadas na quantização
+pub const CLOCK_SYNC_SAMPLES: usize = 64;
+pub const CLICK_MS: f32 = 30.0;
+pub const CLICK_FREQUENCY: f32 = 1000.0;
+pub const CLICK_ACCENT_FREQUENCY: f32 = 1500.0;
+pub const CLICK_LEVEL: f32 = 0.3;
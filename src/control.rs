@@ -0,0 +1,391 @@
+use std::error::Error;
+use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crate::{
+    command::{Command, execute},
+    config::CONTROL_MAX_LINE,
+    debug::DebugManager,
+    events::{FrameEvent, markers_json, number, string},
+    osc::text_command,
+    theremin::ThereminController,
+};
+
+struct ControlClient {
+    stream: UnixStream,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    closed: bool,
+}
+
+impl ControlClient {
+    fn read_lines(&mut self) -> Vec<String> {
+        let mut buffer = [0u8; 1024];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(size) => self.input.extend_from_slice(&buffer[..size]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => {
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+
+        let mut lines = Vec::new();
+        while let Some(end) = self.input.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.input.drain(..=end).collect();
+            lines.push(String::from_utf8_lossy(&line).trim().to_string());
+        }
+
+        if self.input.len() > CONTROL_MAX_LINE {
+            self.closed = true;
+        }
+        lines
+    }
+
+    fn reply(&mut self, response: &str) {
+        self.output.extend_from_slice(response.as_bytes());
+        self.output.push(b'\n');
+    }
+
+    fn flush(&mut self) {
+        while !self.output.is_empty() {
+            match self.stream.write(&self.output) {
+                Ok(0) => {
+                    self.closed = true;
+                    return;
+                }
+                Ok(size) => {
+                    self.output.drain(..size);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                Err(_) => {
+                    self.closed = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// API de controle num socket Unix: uma requisição por linha, uma resposta JSON por linha.
+///
+/// consultas: status, enabled, frequency, amplitude, markers, frame.
+/// comandos: os endereços do OSC, com ou sem o prefixo /theremin/
+/// (ex. "sound 0", "scale Maior", "root -2", "continuous 1", "quit"); cada comando é
+/// executado antes da próxima linha e respondido com o status resultante
+pub struct ControlServer {
+    listener: UnixListener,
+    path: String,
+    clients: Vec<ControlClient>,
+    last_markers: String,
+    last_frame: String,
+}
+
+impl ControlServer {
+    pub fn bind(path: &str) -> Result<Self, Box<dyn Error>> {
+        // arquivo que sobrou de uma execução anterior: só remove se ninguém atende nele
+        if fs::metadata(path).is_ok() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(format!("socket em uso: {}", path).into());
+            }
+            fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(ControlServer {
+            listener,
+            path: path.to_string(),
+            clients: Vec::new(),
+            last_markers: "[]".to_string(),
+            last_frame: "null".to_string(),
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// guarda o último quadro para as consultas markers e frame
+    pub fn update(&mut self, event: &FrameEvent) {
+        self.last_markers = markers_json(event.markers);
+        self.last_frame = event.to_json();
+    }
+
+    /// atende as linhas recebidas; devolve false quando um cliente pediu para sair
+    pub fn poll(
+        &mut self,
+        controller: &mut ThereminController,
+        debug_manager: &mut DebugManager,
+    ) -> bool {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => match stream.set_nonblocking(true) {
+                    Ok(()) => self.clients.push(ControlClient {
+                        stream,
+                        input: Vec::new(),
+                        output: Vec::new(),
+                        closed: false,
+                    }),
+                    Err(e) => eprintln!("[ERROR] Erro ao configurar cliente de controle: {}", e),
+                },
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("[ERROR] Erro ao aceitar cliente de controle: {}", e);
+                    break;
+                }
+            }
+        }
+
+        let mut running = true;
+        for index in 0..self.clients.len() {
+            for line in self.clients[index].read_lines() {
+                if line.is_empty() || !running {
+                    continue;
+                }
+                let response = self.respond(&line, controller, debug_manager, &mut running);
+                self.clients[index].reply(&response);
+            }
+            self.clients[index].flush();
+        }
+        self.clients.retain(|client| !client.closed);
+
+        running
+    }
+
+    fn respond(
+        &self,
+        line: &str,
+        controller: &mut ThereminController,
+        debug_manager: &mut DebugManager,
+        running: &mut bool,
+    ) -> String {
+        let request = match parse_request(line) {
+            Ok(request) => request,
+            Err(message) => return error(&message),
+        };
+
+        match request {
+            Request::Status => {
+                let status = Status::read(controller, debug_manager);
+                format!("{{\"ok\":true,{}}}", status.to_json())
+            }
+            Request::Enabled => {
+                format!("{{\"ok\":true,\"enabled\":{}}}", controller.is_enabled())
+            }
+            Request::Frequency => format!(
+                "{{\"ok\":true,\"frequency\":{}}}",
+                number(controller.get_frequency())
+            ),
+            Request::Amplitude => format!(
+                "{{\"ok\":true,\"amplitude\":{}}}",
+                number(controller.get_amplitude())
+            ),
+            Request::Markers => format!("{{\"ok\":true,\"markers\":{}}}", self.last_markers),
+            Request::Frame => format!("{{\"ok\":true,\"frame\":{}}}", self.last_frame),
+            Request::Command(command) => {
+                if let Command::SetVoice(voice) = command
+                    && !controller.is_voice_available(voice)
+                {
+                    return error(&format!("voz indisponível: {}", voice.name()));
+                }
+
+                let midi = (
+                    controller.is_live_midi_enabled(),
+                    controller.is_mpe_enabled(),
+                );
+                if !execute(command, controller, debug_manager) {
+                    // as linhas seguintes não são mais atendidas
+                    *running = false;
+                    return "{\"ok\":true}".to_string();
+                }
+
+                // as saídas MIDI só mudam se a porta abrir
+                let unchanged = match command {
+                    Command::ToggleLiveMidi => controller.is_live_midi_enabled() == midi.0,
+                    Command::ToggleMpe => controller.is_mpe_enabled() == midi.1,
+                    _ => false,
+                };
+                if unchanged {
+                    return error("saída MIDI indisponível");
+                }
+
+                let status = Status::read(controller, debug_manager);
+                format!("{{\"ok\":true,{}}}", status.to_json())
+            }
+        }
+    }
+}
+
+/// uma linha do cliente: consulta ou comando
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Request {
+    Status,
+    Enabled,
+    Frequency,
+    Amplitude,
+    Markers,
+    Frame,
+    Command(Command),
+}
+
+/// interpreta a linha; o erro é a mensagem devolvida ao cliente
+fn parse_request(line: &str) -> Result<Request, String> {
+    let request = match line {
+        "status" => Request::Status,
+        "enabled" => Request::Enabled,
+        "frequency" => Request::Frequency,
+        "amplitude" => Request::Amplitude,
+        "markers" => Request::Markers,
+        "frame" => Request::Frame,
+        _ => {
+            let address = if line.starts_with('/') {
+                line.to_string()
+            } else {
+                format!("/theremin/{}", line)
+            };
+
+            match text_command(&address) {
+                Some(command) => Request::Command(command),
+                None => return Err(format!("comando desconhecido: {}", line)),
+            }
+        }
+    };
+    Ok(request)
+}
+
+fn error(message: &str) -> String {
+    format!("{{\"ok\":false,\"error\":{}}}", string(message))
+}
+
+/// campos da consulta status
+struct Status {
+    enabled: bool,
+    frequency: f32,
+    amplitude: f32,
+    voice: &'static str,
+    mode: &'static str,
+    scale: &'static str,
+    transpose: i32,
+    continuous: bool,
+    debug: bool,
+}
+
+impl Status {
+    fn read(controller: &ThereminController, debug_manager: &DebugManager) -> Self {
+        Status {
+            enabled: controller.is_enabled(),
+            frequency: controller.get_frequency(),
+            amplitude: controller.get_amplitude(),
+            voice: controller.get_voice().name(),
+            mode: controller.play_mode().name(),
+            scale: controller.scale().name(),
+            transpose: controller.pitch_mapping().transpose(),
+            continuous: controller.pitch_mapping().is_continuous(),
+            debug: debug_manager.debug_mode,
+        }
+    }
+
+    /// os campos, sem as chaves
+    fn to_json(&self) -> String {
+        format!(
+            concat!(
+                "\"enabled\":{},\"frequency\":{},\"amplitude\":{},",
+                "\"voice\":{},\"mode\":{},\"scale\":{},\"transpose\":{},",
+                "\"continuous\":{},\"debug\":{}"
+            ),
+            self.enabled,
+            number(self.frequency),
+            number(self.amplitude),
+            string(self.voice),
+            string(self.mode),
+            string(self.scale),
+            self.transpose,
+            self.continuous,
+            self.debug,
+        )
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitch::Scale;
+
+    #[test]
+    fn queries_are_parsed() {
+        assert_eq!(parse_request("status"), Ok(Request::Status));
+        assert_eq!(parse_request("frequency"), Ok(Request::Frequency));
+        assert_eq!(parse_request("markers"), Ok(Request::Markers));
+        assert_eq!(parse_request("frame"), Ok(Request::Frame));
+    }
+
+    #[test]
+    fn commands_accept_the_osc_addresses_with_or_without_prefix() {
+        assert_eq!(
+            parse_request("sound 0"),
+            Ok(Request::Command(Command::SetSound(false)))
+        );
+        assert_eq!(
+            parse_request("/theremin/scale Maior"),
+            Ok(Request::Command(Command::SetScale(Scale::Major)))
+        );
+        assert_eq!(
+            parse_request("root -2"),
+            Ok(Request::Command(Command::SetTranspose(-2)))
+        );
+        assert_eq!(parse_request("quit"), Ok(Request::Command(Command::Quit)));
+    }
+
+    #[test]
+    fn unknown_or_invalid_lines_are_errors() {
+        for line in ["bogus", "tempo nan", "voice 99", "/outro/sound 1"] {
+            assert_eq!(
+                parse_request(line),
+                Err(format!("comando desconhecido: {}", line))
+            );
+        }
+        assert_eq!(
+            error("comando desconhecido: \"x\""),
+            "{\"ok\":false,\"error\":\"comando desconhecido: \\\"x\\\"\"}"
+        );
+    }
+
+    #[test]
+    fn status_reply_lists_every_field() {
+        let status = Status {
+            enabled: true,
+            frequency: 440.0,
+            amplitude: f32::NAN,
+            voice: "Seno",
+            mode: "Theremin",
+            scale: "Maior",
+            transpose: -2,
+            continuous: false,
+            debug: true,
+        };
+
+        assert_eq!(
+            status.to_json(),
+            concat!(
+                "\"enabled\":true,\"frequency\":440,\"amplitude\":null,",
+                "\"voice\":\"Seno\",\"mode\":\"Theremin\",\"scale\":\"Maior\",",
+                "\"transpose\":-2,\"continuous\":false,\"debug\":true"
+            )
+        );
+    }
+}
@@ -4,22 +4,30 @@ use opencv::{
 };
 
 use crate::{
-    config::{COLOR_WHITE, COLOR_YELLOW},
+    config::{COLOR_WHITE, COLOR_YELLOW, EVENT_STREAM_ENABLED, EVENT_STREAM_TARGET},
+    events::{EventStream, FrameEvent},
     ui::DetectedMarker,
 };
 pub struct DebugManager {
     pub debug_mode: bool,
     pub window_name: String,
     pub window_created: bool,
+    /// quando ativo, cada quadro vira uma linha JSON em EVENT_STREAM_TARGET
+    pub event_stream: Option<EventStream>,
 }
 
 impl DebugManager {
     pub fn new() -> Self {
-        DebugManager {
+        let mut manager = DebugManager {
             debug_mode: false,
             window_name: "Debug View".to_string(),
             window_created: false,
+            event_stream: None,
+        };
+        if EVENT_STREAM_ENABLED {
+            manager.open_event_stream();
         }
+        manager
     }
 
     pub fn toggle_debug_mode(&mut self) -> bool {
@@ -27,6 +35,24 @@ impl DebugManager {
         self.debug_mode
     }
 
+    pub fn toggle_event_stream(&mut self) {
+        if self.event_stream.take().is_some() {
+            log!("[OFF] Eventos JSON desativados");
+        } else {
+            self.open_event_stream();
+        }
+    }
+
+    fn open_event_stream(&mut self) {
+        match EventStream::open(EVENT_STREAM_TARGET) {
+            Ok(stream) => {
+                log!("[ON] Eventos JSON em {}", stream.target().name());
+                self.event_stream = Some(stream);
+            }
+            Err(e) => eprintln!("[ERROR] Erro ao abrir fluxo de eventos: {}", e),
+        }
+    }
+
     pub fn draw_debug_overlay(
         &self,
         frame: &mut Mat,
@@ -134,29 +160,31 @@ impl DebugManager {
         Ok(debug_frame)
     }
 
-    pub fn print_debug_info(
-        &self,
-        frame_counter: u32,
-        markers_count: usize,
-        marker_detected: bool,
-        marker_position: Option<(f32, f32)>,
-    ) {
-        if !self.debug_mode || frame_counter % 30 != 0 {
+    pub fn print_debug_info(&mut self, event: &FrameEvent) {
+        if let Some(stream) = &mut self.event_stream
+            && let Err(e) = stream.write(event)
+        {
+            eprintln!("[ERROR] Erro ao gravar evento: {}", e);
+            self.event_stream = None;
+        }
+
+        if !self.debug_mode || event.frame % 30 != 0 {
             return;
         }
 
-        let position_info = if let Some((x, y)) = marker_position {
-            format!("Pos: ({:.3}, {:.3})", x, y)
+        let position = event.position;
+        let position_info = if position.detected {
+            format!("Pos: ({:.3}, {:.3})", position.x, position.y)
         } else {
             "Pos: N/A".to_string()
         };
 
-        println!(
+        log!(
             "[DEBUG] Frame: {} | {} | Marcadores: {} | Detectado: {}",
-            frame_counter,
+            event.frame,
             position_info,
-            markers_count,
-            if marker_detected { "SIM" } else { "NÃO" }
+            event.markers.len(),
+            if position.detected { "SIM" } else { "NÃO" }
         );
     }
 }
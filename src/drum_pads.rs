@@ -0,0 +1,161 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    config::{DRUM_HYSTERESIS, DRUM_MAX_VOICES},
+    sample::SampleBuffer,
+};
+
+/// zona retangular em coordenadas normalizadas [-1, 1]
+#[derive(Debug, Clone)]
+pub struct DrumZone {
+    pub name: String,
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl DrumZone {
+    /// `margin` positivo expande a zona, negativo a encolhe
+    pub fn contains(&self, x: f32, y: f32, margin: f32) -> bool {
+        x >= self.left - margin
+            && x <= self.right + margin
+            && y >= self.top - margin
+            && y <= self.bottom + margin
+    }
+}
+
+/// detecção de entrada nas zonas com histerese para não redisparar na borda
+pub struct DrumPads {
+    zones: Vec<DrumZone>,
+    active_zone: Option<usize>,
+}
+
+impl DrumPads {
+    pub fn zones(&self) -> &[DrumZone] {
+        &self.zones
+    }
+
+    pub fn active_zone(&self) -> Option<usize> {
+        self.active_zone
+    }
+
+    /// retorna a zona disparada quando o marcador acaba de entrar nela
+    pub fn update(&mut self, x: f32, y: f32) -> Option<usize> {
+        if let Some(active) = self.active_zone {
+            // só libera depois de sair da zona expandida
+            if self.zones[active].contains(x, y, DRUM_HYSTERESIS) {
+                return None;
+            }
+            self.active_zone = None;
+        }
+
+        // só dispara depois de entrar na zona encolhida
+        let entered = self
+            .zones
+            .iter()
+            .position(|zone| zone.contains(x, y, -DRUM_HYSTERESIS))?;
+        self.active_zone = Some(entered);
+        Some(entered)
+    }
+
+    pub fn release(&mut self) {
+        self.active_zone = None;
+    }
+}
+
+struct OneShotVoice {
+    sample: usize,
+    position: f64,
+}
+
+/// toca amostras one-shot disparadas pela thread principal
+pub struct OneShotPlayer {
+    samples: Vec<Option<Arc<SampleBuffer>>>,
+    pending: Arc<Mutex<Vec<usize>>>,
+    voices: Vec<OneShotVoice>,
+}
+
+impl OneShotPlayer {
+    pub fn trigger(&self, sample: usize) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.push(sample);
+    }
+
+    pub fn next_sample(&mut self, output_rate: u32) -> f32 {
+        if let Ok(mut pending) = self.pending.try_lock() {
+            for sample in pending.drain(..) {
+                if self.voices.len() >= DRUM_MAX_VOICES {
+                    self.voices.remove(0);
+                }
+                self.voices.push(OneShotVoice {
+                    sample,
+                    position: 0.0,
+                });
+            }
+        }
+
+        let mut output = 0.0;
+        for voice in &mut self.voices {
+            if let Some(Some(buffer)) = self.samples.get(voice.sample) {
+                output += buffer.read(voice.position);
+                voice.position += buffer.sample_rate as f64 / output_rate as f64;
+            }
+        }
+
+        let samples = &self.samples;
+        self.voices.retain(|voice| match samples.get(voice.sample) {
+            Some(Some(buffer)) => voice.position < buffer.data.len() as f64,
+            _ => false,
+        });
+
+        output
+    }
+}
+
+impl Clone for OneShotPlayer {
+    fn clone(&self) -> Self {
+        // vozes tocando não são compartilhadas, apenas a fila de disparos
+        Self {
+            samples: self.samples.clone(),
+            pending: self.pending.clone(),
+            voices: Vec::new(),
+        }
+    }
+}
+
+/// monta as zonas e carrega as amostras (nome, esquerda, topo, direita, base, arquivo)
+pub fn load_drum_kit(zones: &[(&str, f32, f32, f32, f32, &str)]) -> (DrumPads, OneShotPlayer) {
+    let mut drum_zones = Vec::new();
+    let mut samples = Vec::new();
+
+    for &(name, left, top, right, bottom, path) in zones {
+        drum_zones.push(DrumZone {
+            name: name.to_string(),
+            left,
+            top,
+            right,
+            bottom,
+        });
+
+        match SampleBuffer::load(path) {
+            Ok(buffer) => samples.push(Some(Arc::new(buffer))),
+            Err(e) => {
+                eprintln!("[ERROR] Erro ao carregar pad {} ({}): {}", name, path, e);
+                samples.push(None);
+            }
+        }
+    }
+
+    let pads = DrumPads {
+        zones: drum_zones,
+        active_zone: None,
+    };
+    let player = OneShotPlayer {
+        samples,
+        pending: Arc::new(Mutex::new(Vec::new())),
+        voices: Vec::new(),
+    };
+
+    (pads, player)
+}
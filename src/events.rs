@@ -0,0 +1,180 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{Write, stdout};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    config::EVENT_STREAM_PATH,
+    logging::set_to_stderr,
+    theremin::ThereminController,
+    ui::{DetectedMarker, NormalizedPosition},
+};
+
+/// para onde vão as linhas JSON
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventTarget {
+    /// para usar num pipe; enquanto isso os logs vão para o stderr
+    Stdout,
+    /// arquivo EVENT_STREAM_PATH, recriado a cada abertura
+    File,
+}
+
+impl EventTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventTarget::Stdout => "stdout",
+            EventTarget::File => EVENT_STREAM_PATH,
+        }
+    }
+}
+
+/// retrato de um quadro: rastreamento + parâmetros de áudio
+pub struct FrameEvent<'a> {
+    pub frame: u32,
+    /// segundos desde a época Unix
+    pub timestamp: f64,
+    pub markers: &'a [DetectedMarker],
+    /// posição do marcador 0, a que dirige o som
+    pub position: &'a NormalizedPosition,
+    pub controller: &'a ThereminController,
+}
+
+impl<'a> FrameEvent<'a> {
+    pub fn new(
+        frame: u32,
+        markers: &'a [DetectedMarker],
+        position: &'a NormalizedPosition,
+        controller: &'a ThereminController,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+
+        FrameEvent {
+            frame,
+            timestamp,
+            markers,
+            position,
+            controller,
+        }
+    }
+
+    /// objeto JSON numa linha só
+    pub fn to_json(&self) -> String {
+        let position = self.position;
+        let controller = self.controller;
+
+        format!(
+            concat!(
+                "{{\"frame\":{},\"timestamp\":{:.3},\"markers\":{},",
+                "\"position\":{{\"x\":{},\"y\":{},\"rotation\":{},\"size\":{},\"detected\":{}}},",
+                "\"audio\":{{\"enabled\":{},\"frequency\":{},\"amplitude\":{},\"voice\":{},",
+                "\"mode\":{},\"scale\":{},\"transpose\":{}}}}}"
+            ),
+            self.frame,
+            self.timestamp,
+            markers_json(self.markers),
+            number(position.x),
+            number(position.y),
+            number(position.rotation),
+            number(position.size),
+            position.detected,
+            controller.is_enabled(),
+            number(controller.get_frequency()),
+            number(controller.get_amplitude()),
+            string(controller.get_voice().name()),
+            string(controller.play_mode().name()),
+            string(controller.scale().name()),
+            controller.pitch_mapping().transpose(),
+        )
+    }
+}
+
+/// marcadores como array JSON: id, cantos e centro em pixels
+pub fn markers_json(markers: &[DetectedMarker]) -> String {
+    let markers: Vec<String> = markers
+        .iter()
+        .map(|marker| {
+            let corners: Vec<String> = marker
+                .corners
+                .iter()
+                .map(|corner| format!("[{},{}]", number(corner.x), number(corner.y)))
+                .collect();
+            format!(
+                "{{\"id\":{},\"corners\":[{}],\"center\":[{},{}]}}",
+                marker.id,
+                corners.join(","),
+                number(marker.center.x),
+                number(marker.center.y)
+            )
+        })
+        .collect();
+    format!("[{}]", markers.join(","))
+}
+
+/// número JSON; NaN e infinito não existem em JSON e viram null
+pub fn number(value: f32) -> String {
+    if value.is_finite() {
+        format!("{}", value)
+    } else {
+        "null".to_string()
+    }
+}
+
+/// string JSON com aspas e escapes
+pub fn string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// fluxo de eventos em JSON lines, um objeto por quadro
+pub struct EventStream {
+    writer: Box<dyn Write>,
+    target: EventTarget,
+}
+
+impl EventStream {
+    pub fn open(target: EventTarget) -> Result<Self, Box<dyn Error>> {
+        let writer: Box<dyn Write> = match target {
+            EventTarget::Stdout => {
+                set_to_stderr(true);
+                Box::new(stdout())
+            }
+            EventTarget::File => Box::new(File::create(EVENT_STREAM_PATH)?),
+        };
+        Ok(EventStream { writer, target })
+    }
+
+    pub fn target(&self) -> EventTarget {
+        self.target
+    }
+
+    pub fn write(&mut self, event: &FrameEvent) -> Result<(), Box<dyn Error>> {
+        // linha inteira de uma vez, para quem lê pelo pipe não ver objetos cortados
+        let line = event.to_json() + "\n";
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        // stdout livre de novo: os logs voltam para ele
+        if self.target == EventTarget::Stdout {
+            set_to_stderr(false);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::f32::consts::PI;
+
+use crate::config::{FORMANT_GAIN, FORMANT_UPDATE_SAMPLES};
+
+const VOWEL_NAMES: &[&str] = &["a", "e", "i", "o", "u"];
+
+/// três formantes por vogal: (frequência em Hz, ganho em dB, largura de banda em Hz)
+const VOWELS: [[(f32, f32, f32); 3]; 5] = [
+    [
+        (600.0, 0.0, 60.0),
+        (1040.0, -7.0, 70.0),
+        (2250.0, -9.0, 110.0),
+    ],
+    [
+        (400.0, 0.0, 40.0),
+        (1620.0, -12.0, 80.0),
+        (2400.0, -9.0, 100.0),
+    ],
+    [
+        (250.0, 0.0, 60.0),
+        (1750.0, -30.0, 90.0),
+        (2600.0, -16.0, 100.0),
+    ],
+    [
+        (400.0, 0.0, 40.0),
+        (750.0, -11.0, 80.0),
+        (2400.0, -21.0, 100.0),
+    ],
+    [
+        (350.0, 0.0, 40.0),
+        (600.0, -20.0, 80.0),
+        (2400.0, -32.0, 100.0),
+    ],
+];
+
+/// suavização da vogal entre atualizações dos coeficientes
+const POSITION_SMOOTHING: f32 = 0.2;
+
+/// dimensão do rastreamento que escolhe a vogal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormantAxis {
+    Off,
+    X,
+    /// rotação do marcador no plano da imagem
+    Rotation,
+}
+
+impl FormantAxis {
+    pub const ALL: &[FormantAxis] = &[FormantAxis::Off, FormantAxis::X, FormantAxis::Rotation];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FormantAxis::Off => "Desligado",
+            FormantAxis::X => "X",
+            FormantAxis::Rotation => "Rotacao",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = FormantAxis::ALL
+            .iter()
+            .position(|axis| axis == self)
+            .unwrap_or(0);
+        FormantAxis::ALL[(index + 1) % FormantAxis::ALL.len()]
+    }
+}
+
+/// vogal mais próxima de `position` em [0, 1] (a-e-i-o-u)
+pub fn vowel_name(position: f32) -> &'static str {
+    let index = (position.clamp(0.0, 1.0) * (VOWEL_NAMES.len() - 1) as f32).round() as usize;
+    VOWEL_NAMES[index]
+}
+
+/// passa-banda de ganho unitário no pico (RBJ)
+#[derive(Debug, Clone, Copy, Default)]
+struct BandPass {
+    b0: f32,
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl BandPass {
+    fn set(&mut self, frequency: f32, bandwidth: f32, sample_rate: u32) {
+        let w0 = 2.0 * PI * frequency / sample_rate as f32;
+        let q = frequency / bandwidth;
+        let alpha = w0.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha;
+
+        self.b0 = alpha / a0;
+        self.a1 = -2.0 * w0.cos() / a0;
+        self.a2 = (1.0 - alpha) / a0;
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        // forma direta II transposta, com b1 = 0 e b2 = -b0
+        let output = self.b0 * input + self.z1;
+        self.z1 = -self.a1 * output + self.z2;
+        self.z2 = -self.b0 * input - self.a2 * output;
+        output
+    }
+}
+
+/// banco de três passa-bandas em paralelo que passa continuamente entre as vogais
+#[derive(Clone)]
+pub struct FormantFilter {
+    bands: [BandPass; 3],
+    gains: [f32; 3],
+    position: f32,
+    counter: usize,
+}
+
+impl FormantFilter {
+    pub fn new() -> Self {
+        FormantFilter {
+            bands: [BandPass::default(); 3],
+            gains: [0.0; 3],
+            position: 0.0,
+            counter: 0,
+        }
+    }
+
+    /// `position` em [0, 1] percorre a-e-i-o-u
+    pub fn process(&mut self, input: f32, position: f32, sample_rate: u32) -> f32 {
+        if self.counter == 0 {
+            self.position += (position.clamp(0.0, 1.0) - self.position) * POSITION_SMOOTHING;
+            self.update_coefficients(sample_rate);
+        }
+        self.counter = (self.counter + 1) % FORMANT_UPDATE_SAMPLES;
+
+        let output: f32 = self
+            .bands
+            .iter_mut()
+            .zip(self.gains)
+            .map(|(band, gain)| band.process(input) * gain)
+            .sum();
+
+        output * FORMANT_GAIN
+    }
+
+    fn update_coefficients(&mut self, sample_rate: u32) {
+        let scaled = self.position * (VOWELS.len() - 1) as f32;
+        let index = (scaled as usize).min(VOWELS.len() - 2);
+        let t = scaled - index as f32;
+
+        for (formant, (band, gain)) in self.bands.iter_mut().zip(&mut self.gains).enumerate() {
+            let (f1, db1, bw1) = VOWELS[index][formant];
+            let (f2, db2, bw2) = VOWELS[index + 1][formant];
+
+            band.set(f1 + (f2 - f1) * t, bw1 + (bw2 - bw1) * t, sample_rate);
+            *gain = 10f32.powf((db1 + (db2 - db1) * t) / 20.0);
+        }
+    }
+}
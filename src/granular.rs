@@ -0,0 +1,150 @@
+use std::error::Error;
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    config::{
+        GRANULAR_DENSITY, GRANULAR_GRAIN_MS, GRANULAR_MAX_DENSITY, GRANULAR_MAX_GRAINS,
+        GRANULAR_MIN_DENSITY, GRANULAR_ROOT_NOTE, GRANULAR_SPRAY_MS, GRANULAR_SYNC_SAMPLES,
+        PITCH_REFERENCE_A4,
+    },
+    pitch::midi_to_frequency,
+    rng::Rng,
+    sample::SampleBuffer,
+};
+
+/// o que o eixo Y controla no modo granular
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GranularYControl {
+    Pitch,
+    Density,
+}
+
+impl GranularYControl {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GranularYControl::Pitch => "Altura",
+            GranularYControl::Density => "Densidade",
+        }
+    }
+}
+
+struct Grain {
+    position: f64,
+    step: f64,
+    age: usize,
+    length: usize,
+}
+
+/// motor granular: X escolhe a posição de leitura, Y a altura ou a densidade dos grãos
+pub struct GranularEngine {
+    buffer: Arc<SampleBuffer>,
+    y_control: Arc<Mutex<GranularYControl>>,
+    /// cópia de `y_control` da thread de áudio, relida a cada GRANULAR_SYNC_SAMPLES
+    current_y_control: GranularYControl,
+    sync_counter: usize,
+    grains: Vec<Grain>,
+    samples_until_next: usize,
+    rng: Rng,
+}
+
+impl GranularEngine {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let buffer = SampleBuffer::load(path)?;
+
+        Ok(GranularEngine {
+            buffer: Arc::new(buffer),
+            y_control: Arc::new(Mutex::new(GranularYControl::Pitch)),
+            current_y_control: GranularYControl::Pitch,
+            sync_counter: 0,
+            grains: Vec::with_capacity(GRANULAR_MAX_GRAINS),
+            samples_until_next: 0,
+            rng: Rng::new(),
+        })
+    }
+
+    pub fn set_y_control(&self, control: GranularYControl) {
+        let mut y_control = self.y_control.lock().unwrap();
+        *y_control = control;
+    }
+
+    pub fn get_y_control(&self) -> GranularYControl {
+        let y_control = self.y_control.lock().unwrap();
+        *y_control
+    }
+
+    pub fn next_sample(&mut self, x: f32, y: f32, frequency: f32, output_rate: u32) -> f32 {
+        if self.sync_counter == 0 {
+            self.current_y_control = self.get_y_control();
+        }
+        self.sync_counter = (self.sync_counter + 1) % GRANULAR_SYNC_SAMPLES;
+
+        let (rate, density) = match self.current_y_control {
+            GranularYControl::Pitch => (
+                frequency / midi_to_frequency(GRANULAR_ROOT_NOTE as f32, PITCH_REFERENCE_A4),
+                GRANULAR_DENSITY,
+            ),
+            GranularYControl::Density => {
+                let t = ((y + 1.0) / 2.0).clamp(0.0, 1.0);
+                (
+                    1.0,
+                    GRANULAR_MIN_DENSITY + (GRANULAR_MAX_DENSITY - GRANULAR_MIN_DENSITY) * t,
+                )
+            }
+        };
+
+        if self.samples_until_next == 0 {
+            self.spawn_grain(x, rate, output_rate);
+            self.samples_until_next = (output_rate as f32 / density.max(0.1)) as usize;
+        }
+        self.samples_until_next -= 1;
+
+        let mut output = 0.0;
+        for grain in &mut self.grains {
+            let window = 0.5 - 0.5 * (2.0 * PI * grain.age as f32 / grain.length as f32).cos();
+            output += self.buffer.read(grain.position) * window;
+            grain.position += grain.step;
+            grain.age += 1;
+        }
+        self.grains.retain(|grain| grain.age < grain.length);
+
+        // compensa a sobreposição média dos grãos
+        let overlap = (density * GRANULAR_GRAIN_MS / 1000.0).max(1.0);
+        output / overlap.sqrt()
+    }
+
+    fn spawn_grain(&mut self, x: f32, rate: f32, output_rate: u32) {
+        if self.grains.len() >= GRANULAR_MAX_GRAINS {
+            return;
+        }
+
+        let buffer_rate = self.buffer.sample_rate as f64;
+        let length = self.buffer.data.len() as f64;
+        let spray = GRANULAR_SPRAY_MS as f64 * buffer_rate / 1000.0;
+
+        let center = ((x + 1.0) / 2.0).clamp(0.0, 1.0) as f64 * length;
+        let position = (center + self.rng.next_bipolar() as f64 * spray).clamp(0.0, length - 1.0);
+
+        self.grains.push(Grain {
+            position,
+            step: rate as f64 * buffer_rate / output_rate as f64,
+            age: 0,
+            length: ((GRANULAR_GRAIN_MS / 1000.0 * output_rate as f32) as usize).max(1),
+        });
+    }
+}
+
+impl Clone for GranularEngine {
+    fn clone(&self) -> Self {
+        // grãos em andamento não são compartilhados
+        Self {
+            buffer: self.buffer.clone(),
+            y_control: self.y_control.clone(),
+            current_y_control: self.current_y_control,
+            sync_counter: 0,
+            grains: Vec::with_capacity(GRANULAR_MAX_GRAINS),
+            samples_until_next: 0,
+            rng: Rng::new(),
+        }
+    }
+}
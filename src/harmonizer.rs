@@ -0,0 +1,114 @@
+use crate::{config::HARMONY_DETUNE_CENTS, pitch::PitchMapping};
+
+/// intervalo da voz paralela em relação à voz principal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HarmonyInterval {
+    Off,
+    /// intervalo fixo em semitons
+    Fixed(i32),
+    /// graus acima contados na escala de sete notas da escala atual, para que terças e
+    /// quintas continuem terças e quintas nas pentatônicas
+    Diatonic(usize),
+}
+
+impl HarmonyInterval {
+    pub const ALL: &[HarmonyInterval] = &[
+        HarmonyInterval::Off,
+        HarmonyInterval::Fixed(4),
+        HarmonyInterval::Fixed(7),
+        HarmonyInterval::Fixed(12),
+        HarmonyInterval::Diatonic(2),
+        HarmonyInterval::Diatonic(4),
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HarmonyInterval::Off => "Desligado",
+            HarmonyInterval::Fixed(4) => "3a maior",
+            HarmonyInterval::Fixed(7) => "5a justa",
+            HarmonyInterval::Fixed(12) => "Oitava",
+            HarmonyInterval::Fixed(_) => "Fixo",
+            HarmonyInterval::Diatonic(2) => "3a na escala",
+            HarmonyInterval::Diatonic(4) => "5a na escala",
+            HarmonyInterval::Diatonic(_) => "Na escala",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = HarmonyInterval::ALL
+            .iter()
+            .position(|interval| interval == self)
+            .unwrap_or(0);
+        HarmonyInterval::ALL[(index + 1) % HarmonyInterval::ALL.len()]
+    }
+
+    /// razão de frequência da voz paralela para a nota da posição Y, já com o detune;
+    /// `None` se o harmonizador estiver desligado
+    pub fn ratio(&self, mapping: &PitchMapping, y: f32) -> Option<f32> {
+        let cents = self.semitones(mapping, y)? as f32 * 100.0 + HARMONY_DETUNE_CENTS;
+        Some(2f32.powf(cents / 1200.0))
+    }
+
+    /// intervalo em semitons acima da nota da posição Y
+    fn semitones(&self, mapping: &PitchMapping, y: f32) -> Option<i32> {
+        match *self {
+            HarmonyInterval::Off => None,
+            HarmonyInterval::Fixed(semitones) => Some(semitones),
+            HarmonyInterval::Diatonic(steps) => {
+                // as notas da pentatônica também estão na escala de sete notas
+                let parent = mapping.scale().heptatonic();
+                let note = mapping.degree_to_note(mapping.index_for_position(y));
+                let degree = (0..parent.intervals().len() * (mapping.octaves() as usize + 1))
+                    .find(|&degree| mapping.degree_to_note_in(parent, degree) >= note)?;
+                Some(mapping.degree_to_note_in(parent, degree + steps) - note)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitch::Scale;
+
+    /// intervalos para cada faixa de Y da escala
+    fn intervals(interval: HarmonyInterval, scale: Scale) -> Vec<i32> {
+        let mapping = PitchMapping::new(48, 1, 440.0, 0, scale);
+        let steps = scale.intervals().len();
+        (0..steps)
+            .map(|index| {
+                let y = (index as f32 + 0.5) / steps as f32 * 2.0 - 1.0;
+                interval.semitones(&mapping, y).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diatonic_third_on_pentatonic_is_a_third() {
+        // C D E G A: terças de C maior
+        assert_eq!(
+            intervals(HarmonyInterval::Diatonic(2), Scale::PentatonicMajor),
+            vec![4, 3, 3, 4, 3]
+        );
+        // A C D E G: terças de A menor
+        assert_eq!(
+            intervals(HarmonyInterval::Diatonic(2), Scale::PentatonicMinor),
+            vec![3, 4, 3, 3, 4]
+        );
+    }
+
+    #[test]
+    fn diatonic_fifth_on_major_scale() {
+        assert_eq!(
+            intervals(HarmonyInterval::Diatonic(4), Scale::Major),
+            vec![7, 7, 7, 7, 7, 7, 6] // B-F diminuta
+        );
+    }
+
+    #[test]
+    fn fixed_and_off() {
+        let mapping = PitchMapping::new(48, 1, 440.0, 0, Scale::Major);
+        assert_eq!(HarmonyInterval::Fixed(7).semitones(&mapping, 0.0), Some(7));
+        assert_eq!(HarmonyInterval::Off.ratio(&mapping, 0.0), None);
+    }
+}
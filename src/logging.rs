@@ -0,0 +1,24 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// ligado enquanto os eventos JSON ocupam o stdout
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_to_stderr(enabled: bool) {
+    TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+pub fn to_stderr() -> bool {
+    TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// println! dos logs: vai para o stderr enquanto o stdout transporta os eventos JSON,
+/// para quem lê pelo pipe receber só as linhas JSON
+macro_rules! log {
+    ($($arg:tt)*) => {
+        if $crate::logging::to_stderr() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
@@ -0,0 +1,189 @@
+use std::sync::{Arc, Mutex};
+
+use crate::config::{LOOPER_MAX_SECONDS, LOOPER_OVERDUB_FEEDBACK, LOOPER_SYNC_SAMPLES};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LooperMode {
+    Empty,
+    Recording,
+    Playing,
+    Overdubbing,
+    Stopped,
+}
+
+impl LooperMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LooperMode::Empty => "Vazio",
+            LooperMode::Recording => "Gravando",
+            LooperMode::Playing => "Tocando",
+            LooperMode::Overdubbing => "Sobrepondo",
+            LooperMode::Stopped => "Parado",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LooperCommand {
+    /// inicia a gravação ou, se já estiver gravando, fecha o loop
+    Record,
+    Overdub,
+    TogglePlay,
+    Clear,
+}
+
+/// estado do looper visto pela interface (tempos em segundos)
+#[derive(Debug, Clone, Copy)]
+pub struct LooperStatus {
+    pub mode: LooperMode,
+    pub position: f32,
+    pub length: f32,
+    pub max_length: f32,
+}
+
+struct LooperShared {
+    commands: Vec<LooperCommand>,
+    status: LooperStatus,
+}
+
+/// grava a saída sintetizada num buffer circular e a devolve por baixo da voz ao vivo
+pub struct Looper {
+    shared: Arc<Mutex<LooperShared>>,
+    samples_per_second: usize,
+    buffer: Vec<f32>,
+    mode: LooperMode,
+    length: usize,
+    position: usize,
+    sync_counter: usize,
+}
+
+impl Looper {
+    pub fn new(sample_rate: u32) -> Self {
+        let samples_per_second = sample_rate as usize;
+        let shared = Arc::new(Mutex::new(LooperShared {
+            commands: Vec::new(),
+            status: LooperStatus {
+                mode: LooperMode::Empty,
+                position: 0.0,
+                length: 0.0,
+                max_length: LOOPER_MAX_SECONDS,
+            },
+        }));
+        Self::with_shared(shared, samples_per_second)
+    }
+
+    fn with_shared(shared: Arc<Mutex<LooperShared>>, samples_per_second: usize) -> Self {
+        let capacity = (LOOPER_MAX_SECONDS * samples_per_second as f32) as usize;
+
+        Looper {
+            shared,
+            samples_per_second,
+            buffer: vec![0.0; capacity],
+            mode: LooperMode::Empty,
+            length: 0,
+            position: 0,
+            sync_counter: 0,
+        }
+    }
+
+    pub fn send(&self, command: LooperCommand) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.commands.push(command);
+    }
+
+    pub fn status(&self) -> LooperStatus {
+        let shared = self.shared.lock().unwrap();
+        shared.status
+    }
+
+    /// grava/sobrepõe `live` e retorna a amostra do loop na posição atual
+    pub fn process(&mut self, live: f32) -> f32 {
+        if self.sync_counter == 0 {
+            self.sync();
+        }
+        self.sync_counter = (self.sync_counter + 1) % LOOPER_SYNC_SAMPLES;
+
+        match self.mode {
+            LooperMode::Recording => {
+                self.buffer[self.position] = live;
+                self.position += 1;
+                if self.position >= self.buffer.len() {
+                    self.close_loop();
+                }
+                0.0
+            }
+            LooperMode::Playing => {
+                let output = self.buffer[self.position];
+                self.position = (self.position + 1) % self.length;
+                output
+            }
+            LooperMode::Overdubbing => {
+                let output = self.buffer[self.position];
+                self.buffer[self.position] = output * LOOPER_OVERDUB_FEEDBACK + live;
+                self.position = (self.position + 1) % self.length;
+                output
+            }
+            LooperMode::Empty | LooperMode::Stopped => 0.0,
+        }
+    }
+
+    /// aplica os comandos pendentes e publica o estado para a interface
+    fn sync(&mut self) {
+        let commands = std::mem::take(&mut self.shared.lock().unwrap().commands);
+
+        for command in commands {
+            match (command, self.mode) {
+                (LooperCommand::Record, LooperMode::Recording) => self.close_loop(),
+                (LooperCommand::Record, _) => {
+                    self.mode = LooperMode::Recording;
+                    self.position = 0;
+                    self.length = 0;
+                }
+                (LooperCommand::Overdub, LooperMode::Playing) => {
+                    self.mode = LooperMode::Overdubbing;
+                }
+                (LooperCommand::Overdub, LooperMode::Overdubbing) => {
+                    self.mode = LooperMode::Playing;
+                }
+                (LooperCommand::TogglePlay, LooperMode::Recording) => self.close_loop(),
+                (LooperCommand::TogglePlay, LooperMode::Playing | LooperMode::Overdubbing) => {
+                    self.mode = LooperMode::Stopped;
+                }
+                (LooperCommand::TogglePlay, LooperMode::Stopped) => {
+                    self.mode = LooperMode::Playing;
+                    self.position = 0;
+                }
+                (LooperCommand::Clear, _) => {
+                    self.buffer.fill(0.0);
+                    self.mode = LooperMode::Empty;
+                    self.position = 0;
+                    self.length = 0;
+                }
+                _ => {}
+            }
+        }
+
+        let seconds = self.samples_per_second as f32;
+        let mut shared = self.shared.lock().unwrap();
+        shared.status.mode = self.mode;
+        shared.status.position = self.position as f32 / seconds;
+        shared.status.length = self.length as f32 / seconds;
+    }
+
+    fn close_loop(&mut self) {
+        self.length = self.position;
+        self.position = 0;
+        self.mode = if self.length > 0 {
+            LooperMode::Playing
+        } else {
+            LooperMode::Empty
+        };
+    }
+}
+
+impl Clone for Looper {
+    fn clone(&self) -> Self {
+        // o buffer gravado pertence à instância que está tocando
+        Self::with_shared(self.shared.clone(), self.samples_per_second)
+    }
+}
@@ -1,12 +1,47 @@
+// macro log! usada pelos módulos abaixo
+#[macro_use]
+mod logging;
+
+mod amplitude;
+mod arpeggiator;
 mod aruco;
+mod chord;
+mod clock;
 mod camera;
+mod command;
 mod config;
+#[cfg(unix)]
+mod control;
 mod debug;
+mod drum_pads;
+mod events;
+mod formant;
+mod granular;
+mod harmonizer;
+mod looper;
+mod marker_loss;
+mod master_bus;
+mod midi;
+mod midi_file;
+mod midi_in;
+mod midi_out;
+mod mpe;
+mod noise;
+mod osc;
+mod pitch;
+mod pluck;
+mod rng;
+mod sample;
+mod sampler;
 mod theremin;
 mod ui;
+mod websocket;
 
 use aruco::ArucoProcessor;
+use command::{Command, execute};
 use debug::DebugManager;
+use events::FrameEvent;
+use midi_in::MidiControl;
 use opencv::{
     core::Mat,
     highgui::{WINDOW_AUTOSIZE, destroy_window, imshow, named_window, wait_key},
@@ -14,29 +49,139 @@ use opencv::{
 };
 use std::error::Error;
 use theremin::ThereminController;
-use ui::{draw_markers, draw_position_info, draw_theremin_info, draw_debug_status};
+use ui::{
+    draw_beat_indicator, draw_debug_status, draw_drum_zones, draw_looper_status, draw_markers,
+    draw_master_meter, draw_position_info, draw_theremin_info, draw_tuner,
+};
+use osc::{OscReceiver, OscSender};
+use websocket::WebSocketServer;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    println!("===== ArUco + Theremin =====");
-    println!("Controles:");
-    println!("  ESC     - Sair");
-    println!("  ESPAÇO  - Ativar/Desativar som");
-    println!("  V       - Alternar visualização debug");
-    println!("============================");
+    log!("===== ArUco + Theremin =====");
+    log!("Controles:");
+    log!("  ESC     - Sair");
+    log!("  ESPAÇO  - Ativar/Desativar som");
+    log!("  V       - Alternar visualização debug");
+    log!("  Y       - Ativar/Desativar eventos JSON por quadro");
+    log!("  C       - Ativar/Desativar compressor");
+    log!("  A       - Alternar curva de amplitude");
+    log!("  W       - Alternar voz (seno/sampler/granular/ruído/corda)");
+    log!("  G       - Granular: Y controla altura/densidade");
+    log!("  N       - Ruído: X controla mistura/cor");
+    log!("  I       - Harmonizador: intervalo da voz paralela");
+    log!("  F       - Filtro de formantes: desligado/X/rotação");
+    log!("  M       - Alternar modo (theremin/pads/arpejo/acorde)");
+    log!("  S       - Alternar escala");
+    log!("  E       - Altura contínua/quantizada");
+    log!("  T       - Acorde: tríade/tétrade");
+    log!("  R       - Loop: gravar/fechar");
+    log!("  O       - Loop: sobrepor");
+    log!("  L       - Loop: tocar/parar");
+    log!("  K       - Loop: apagar");
+    log!("  D       - Exportar performance como arquivo MIDI");
+    log!("  J       - MIDI ao vivo (porta virtual ALSA)");
+    log!("  U       - MPE: um canal por marcador");
+    log!("  H       - Perda do marcador: manter/fade/silenciar/silenciar após tempo");
+    log!("  , / .   - Diminuir/aumentar tempo (BPM)");
+    log!("  B       - Ativar/Desativar metrônomo");
+    log!("  Q       - Quantizar notas ao tempo");
+    log!("  X       - Arpejo: X controla velocidade/padrão");
+    log!("  P       - Arpejo: alternar padrão");
+    log!("  + / -   - Transpor um semitom acima/abaixo");
+    log!("  (as mesmas ações também chegam por OSC e WebSocket em /theremin/...)");
+    log!("  Teclado MIDI: nota grave = tônica, acorde = escala, CCs = efeitos");
+    log!("============================");
 
     let mut theremin_controller = ThereminController::new()?;
-    println!("[START] Theremin inicializado. Som ativo.");
+    log!("[START] Theremin inicializado. Som ativo.");
+
+    let osc_sender = if config::OSC_OUTPUT_ENABLED {
+        match OscSender::open(config::OSC_TARGET) {
+            Ok(sender) => {
+                log!("[START] OSC enviando para {}", sender.target());
+                Some(sender)
+            }
+            Err(e) => {
+                eprintln!("[ERROR] Erro ao abrir saída OSC: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let osc_receiver = if config::OSC_INPUT_ENABLED {
+        match OscReceiver::bind(config::OSC_LISTEN_ADDRESS) {
+            Ok(receiver) => {
+                log!("[START] OSC escutando em {}", receiver.address());
+                Some(receiver)
+            }
+            Err(e) => {
+                eprintln!("[ERROR] Erro ao abrir entrada OSC: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut websocket_server = if config::WEBSOCKET_ENABLED {
+        match WebSocketServer::bind(config::WEBSOCKET_ADDRESS) {
+            Ok(server) => {
+                log!("[START] WebSocket em ws://{}", server.address());
+                Some(server)
+            }
+            Err(e) => {
+                eprintln!("[ERROR] Erro ao abrir servidor WebSocket: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut midi_control = if config::MIDI_INPUT_ENABLED {
+        match midi_in::open_input(config::MIDI_INPUT_BACKEND) {
+            Ok(input) => {
+                let control = MidiControl::new(input);
+                log!("[START] Entrada MIDI: {}", control.name());
+                Some(control)
+            }
+            Err(e) => {
+                eprintln!("[ERROR] Erro ao abrir entrada MIDI: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    #[cfg(unix)]
+    let mut control_server = if config::CONTROL_SOCKET_ENABLED {
+        match control::ControlServer::bind(config::CONTROL_SOCKET_PATH) {
+            Ok(server) => {
+                log!("[START] Socket de controle em {}", server.path());
+                Some(server)
+            }
+            Err(e) => {
+                eprintln!("[ERROR] Erro ao abrir socket de controle: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     let (mut cam, is_camera) = camera::initialize_capture()?;
 
     let mut aruco_processor = match ArucoProcessor::new() {
         Ok(processor) => {
-            println!("[START] Processador ArUco inicializado");
+            log!("[START] Processador ArUco inicializado");
             Some(processor)
         }
         Err(e) => {
-            println!("[ERROR] Erro ao inicializar ArUco: {}", e);
-            println!("[INFO] Continuando apenas com visualização de vídeo...");
+            log!("[ERROR] Erro ao inicializar ArUco: {}", e);
+            log!("[INFO] Continuando apenas com visualização de vídeo...");
             None
         }
     };
@@ -44,18 +189,17 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut debug_manager = DebugManager::new();
     
     named_window("Video", WINDOW_AUTOSIZE)?;
-    println!("[START] Iniciando captura de vídeo...");
-    println!("============================");
+    log!("[START] Iniciando captura de vídeo...");
+    log!("============================");
 
     let mut frame_counter = 0;
-    let mut last_position = (0.0, 0.0);
 
-    loop {
+    'main: loop {
         frame_counter += 1;
 
         let mut frame = Mat::default();
         if !camera::read_frame(&mut cam, &mut frame, is_camera)? {
-            println!("[INFO] Fim do vídeo/câmera");
+            log!("[INFO] Fim do vídeo/câmera");
             break;
         }
 
@@ -65,6 +209,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         if let Some(processor) = &mut aruco_processor {
             match processor.detect_markers(&frame) {
                 Ok((markers, processing_time)) => {
+                    if let Err(e) = draw_drum_zones(&mut frame, &theremin_controller) {
+                        eprintln!("[ERROR] Erro ao desenhar zonas: {}", e);
+                    }
+
                     if let Err(e) = draw_markers(&mut frame, &markers) {
                         eprintln!("[ERROR] Erro ao desenhar marcadores: {}", e);
                     }
@@ -83,7 +231,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                                 match named_window(&debug_manager.window_name, WINDOW_AUTOSIZE) {
                                     Ok(_) => {
                                         debug_manager.window_created = true;
-                                        println!("[DEBUG] Janela de debug criada");
+                                        log!("[DEBUG] Janela de debug criada");
                                     }
                                     Err(e) => eprintln!("[ERROR] Erro ao criar janela de debug: {}", e),
                                 }
@@ -108,7 +256,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                             match destroy_window(&debug_manager.window_name) {
                                 Ok(_) => {
                                     debug_manager.window_created = false;
-                                    println!("[DEBUG] Janela de debug fechada");
+                                    log!("[DEBUG] Janela de debug fechada");
                                 }
                                 Err(e) => {
                                     // ignorar erro se a janela já estiver fechada
@@ -123,30 +271,56 @@ fn main() -> Result<(), Box<dyn Error>> {
                     }
 
                     draw_debug_status(&mut frame, debug_manager.debug_mode)?;
-                    
-                    let marker_pos = if marker_position.detected {
-                        Some((marker_position.x, marker_position.y))
-                    } else {
-                        None
-                    };
-                    
-                    debug_manager.print_debug_info(
-                        frame_counter,
-                        markers.len(),
-                        marker_position.detected,
-                        marker_pos,
-                    );
 
                     // atualiza theremin
+                    let marker_positions = processor.calculate_marker_positions(
+                        frame_width,
+                        frame_height,
+                        &markers,
+                    );
+                    theremin_controller.update_markers(&marker_positions);
+
+                    if let Some(osc) = &osc_sender
+                        && let Err(e) = osc.send_frame(
+                            &markers,
+                            &marker_positions,
+                            frame_width,
+                            frame_height,
+                            &theremin_controller,
+                        )
+                        && frame_counter % 60 == 0
+                    {
+                        eprintln!("[ERROR] Erro ao enviar OSC: {}", e);
+                    }
+
                     if marker_position.detected {
-                        last_position = (marker_position.x, marker_position.y);
+                        theremin_controller.update_rotation(marker_position.rotation);
                         theremin_controller
                             .update_from_position(marker_position.x, marker_position.y);
                     } else {
-                        theremin_controller.update_from_position(last_position.0, last_position.1);
+                        theremin_controller.marker_lost();
+                    }
+
+                    let event = FrameEvent::new(
+                        frame_counter,
+                        &markers,
+                        &marker_position,
+                        &theremin_controller,
+                    );
+                    debug_manager.print_debug_info(&event);
+                    if let Some(server) = &mut websocket_server {
+                        server.broadcast(&event.to_json());
+                    }
+                    #[cfg(unix)]
+                    if let Some(server) = &mut control_server {
+                        server.update(&event);
                     }
 
                     draw_theremin_info(&mut frame, &theremin_controller)?;
+                    draw_master_meter(&mut frame, &theremin_controller)?;
+                    draw_looper_status(&mut frame, &theremin_controller)?;
+                    draw_beat_indicator(&mut frame, &theremin_controller)?;
+                    draw_tuner(&mut frame, &theremin_controller)?;
                 }
                 Err(e) => {
                     if !e.to_string().contains("empty") && frame_counter % 60 == 0 {
@@ -160,31 +334,43 @@ fn main() -> Result<(), Box<dyn Error>> {
 
         let key = wait_key(30)?;
 
-        match key {
-            27 => {
-                // ESC
-                println!("[INFO] Esc pressionado. Saindo...");
-                theremin_controller.stop();
-                break;
-            }
-            32 => {
-                // ESPAÇO
-                theremin_controller.toggle_sound();
-            }
-            86 | 118 => {
-                // 'V' ou 'v' - Alterna modo debug
-                if debug_manager.toggle_debug_mode() {
-                    println!("[DEBUG] Modo debug ATIVADO");
-                } else {
-                    println!("[DEBUG] Modo debug DESATIVADO");
+        // teclado e controle remoto passam pelos mesmos comandos
+        let mut commands: Vec<Command> = Command::from_key(key).into_iter().collect();
+        if let Some(receiver) = &osc_receiver {
+            commands.extend(receiver.poll());
+        }
+        if let Some(server) = &mut websocket_server {
+            commands.extend(server.poll());
+        }
+        if let Some(control) = &mut midi_control {
+            match control.poll() {
+                Ok(midi_commands) => commands.extend(midi_commands),
+                Err(e) => {
+                    eprintln!("[ERROR] Erro na entrada MIDI, desativando: {}", e);
+                    midi_control = None;
                 }
             }
-            _ => {}
+        }
+
+        for command in commands {
+            if !execute(command, &mut theremin_controller, &mut debug_manager) {
+                break 'main;
+            }
+        }
+
+        // executa cada linha antes de responder a próxima
+        #[cfg(unix)]
+        if let Some(server) = &mut control_server
+            && !server.poll(&mut theremin_controller, &mut debug_manager)
+        {
+            break 'main;
         }
     }
 
-    println!("============================");
-    println!("[INFO] Liberando recursos...");
+    log!("============================");
+    log!("[INFO] Liberando recursos...");
+
+    theremin_controller.export_midi();
 
     if debug_manager.window_created {
         let _ = destroy_window(&debug_manager.window_name);
@@ -195,6 +381,6 @@ fn main() -> Result<(), Box<dyn Error>> {
         eprintln!("[ERROR] Erro ao liberar câmera: {}", e);
     }
 
-    println!("[INFO] Programa finalizado com sucesso.");
+    log!("[INFO] Programa finalizado com sucesso.");
     Ok(())
 }
\ No newline at end of file
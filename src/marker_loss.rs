@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use crate::config::{MARKER_LOSS_FADE_MS, MARKER_LOSS_TIMEOUT_MS};
+
+/// o que acontece com o som quando o marcador 0 some da imagem
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkerLossPolicy {
+    /// mantém a última nota indefinidamente
+    Hold,
+    /// abaixa o volume até zero em MARKER_LOSS_FADE_MS
+    FadeOut,
+    Mute,
+    /// mantém a nota por MARKER_LOSS_TIMEOUT_MS e então silencia
+    MuteAfterTimeout,
+}
+
+impl MarkerLossPolicy {
+    pub const ALL: &[MarkerLossPolicy] = &[
+        MarkerLossPolicy::Hold,
+        MarkerLossPolicy::FadeOut,
+        MarkerLossPolicy::Mute,
+        MarkerLossPolicy::MuteAfterTimeout,
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            MarkerLossPolicy::Hold => "Manter".to_string(),
+            MarkerLossPolicy::FadeOut => format!("Fade {:.0} ms", MARKER_LOSS_FADE_MS),
+            MarkerLossPolicy::Mute => "Silenciar".to_string(),
+            MarkerLossPolicy::MuteAfterTimeout => {
+                format!("Silenciar apos {:.0} ms", MARKER_LOSS_TIMEOUT_MS)
+            }
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = MarkerLossPolicy::ALL
+            .iter()
+            .position(|policy| policy == self)
+            .unwrap_or(0);
+        MarkerLossPolicy::ALL[(index + 1) % MarkerLossPolicy::ALL.len()]
+    }
+
+    /// ganho da voz depois de `elapsed` sem ver o marcador
+    pub fn gain(&self, elapsed: Duration) -> f32 {
+        let elapsed_ms = elapsed.as_secs_f32() * 1000.0;
+
+        match self {
+            MarkerLossPolicy::Hold => 1.0,
+            MarkerLossPolicy::FadeOut => (1.0 - elapsed_ms / MARKER_LOSS_FADE_MS).max(0.0),
+            MarkerLossPolicy::Mute => 0.0,
+            MarkerLossPolicy::MuteAfterTimeout => {
+                if elapsed_ms < MARKER_LOSS_TIMEOUT_MS {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
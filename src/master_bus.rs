@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::config::{
+    COMPRESSOR_ATTACK_MS, COMPRESSOR_MAKEUP_DB, COMPRESSOR_RATIO, COMPRESSOR_RELEASE_MS,
+    COMPRESSOR_THRESHOLD_DB, LIMITER_LOOKAHEAD_MS, LIMITER_RELEASE_MS, MASTER_CEILING,
+    METER_BLOCK_SIZE, METER_PEAK_DECAY,
+};
+
+/// níveis do medidor lidos pela interface
+#[derive(Debug, Clone, Copy)]
+pub struct MeterLevels {
+    pub peak: f32,
+    pub rms: f32,
+    pub gain_reduction: f32,
+}
+
+struct MasterState {
+    compressor_enabled: bool,
+    levels: MeterLevels,
+}
+
+/// barramento de saída: compressor opcional + limiter com look-ahead + medidor
+pub struct MasterBus {
+    state: Arc<Mutex<MasterState>>,
+    sample_rate: u32,
+    compressor_enabled: bool,
+    compressor_envelope: f32,
+    compressor_attack: f32,
+    compressor_release: f32,
+    lookahead: u64,
+    delay: VecDeque<f32>,
+    // (índice da amostra, ganho necessário) em ordem crescente de ganho
+    gain_window: VecDeque<(u64, f32)>,
+    sample_index: u64,
+    limiter_gain: f32,
+    limiter_release: f32,
+    block_peak: f32,
+    block_sum_squares: f32,
+    block_len: usize,
+}
+
+impl MasterBus {
+    pub fn new(sample_rate: u32) -> Self {
+        let state = Arc::new(Mutex::new(MasterState {
+            compressor_enabled: false,
+            levels: MeterLevels {
+                peak: 0.0,
+                rms: 0.0,
+                gain_reduction: 1.0,
+            },
+        }));
+        Self::with_state(state, sample_rate)
+    }
+
+    fn with_state(state: Arc<Mutex<MasterState>>, sample_rate: u32) -> Self {
+        let lookahead = ms_to_samples(LIMITER_LOOKAHEAD_MS, sample_rate).max(1);
+        let compressor_enabled = state.lock().unwrap().compressor_enabled;
+
+        Self {
+            state,
+            sample_rate,
+            compressor_enabled,
+            compressor_envelope: 0.0,
+            compressor_attack: time_coefficient(COMPRESSOR_ATTACK_MS, sample_rate),
+            compressor_release: time_coefficient(COMPRESSOR_RELEASE_MS, sample_rate),
+            lookahead: lookahead as u64,
+            delay: VecDeque::with_capacity(lookahead + 1),
+            gain_window: VecDeque::new(),
+            sample_index: 0,
+            limiter_gain: 1.0,
+            limiter_release: time_coefficient(LIMITER_RELEASE_MS, sample_rate),
+            block_peak: 0.0,
+            block_sum_squares: 0.0,
+            block_len: 0,
+        }
+    }
+
+    pub fn set_compressor_enabled(&self, enabled: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.compressor_enabled = enabled;
+    }
+
+    pub fn is_compressor_enabled(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.compressor_enabled
+    }
+
+    pub fn levels(&self) -> MeterLevels {
+        let state = self.state.lock().unwrap();
+        state.levels
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let sample = if self.compressor_enabled {
+            self.compress(input)
+        } else {
+            input
+        };
+
+        let output = self.limit(sample);
+        self.update_meter(output);
+        output
+    }
+
+    fn compress(&mut self, input: f32) -> f32 {
+        let level = input.abs();
+        let coefficient = if level > self.compressor_envelope {
+            self.compressor_attack
+        } else {
+            self.compressor_release
+        };
+        self.compressor_envelope =
+            coefficient * self.compressor_envelope + (1.0 - coefficient) * level;
+
+        let over_db = linear_to_db(self.compressor_envelope) - COMPRESSOR_THRESHOLD_DB;
+        let reduction_db = if over_db > 0.0 {
+            over_db * (1.0 - 1.0 / COMPRESSOR_RATIO)
+        } else {
+            0.0
+        };
+
+        input * db_to_linear(COMPRESSOR_MAKEUP_DB - reduction_db)
+    }
+
+    fn limit(&mut self, input: f32) -> f32 {
+        let level = input.abs();
+        let required = if level > MASTER_CEILING {
+            MASTER_CEILING / level
+        } else {
+            1.0
+        };
+
+        // mínimo deslizante dos ganhos necessários dentro da janela de look-ahead
+        while let Some(&(_, gain)) = self.gain_window.back() {
+            if gain >= required {
+                self.gain_window.pop_back();
+            } else {
+                break;
+            }
+        }
+        self.gain_window.push_back((self.sample_index, required));
+        while let Some(&(index, _)) = self.gain_window.front() {
+            if index + self.lookahead < self.sample_index {
+                self.gain_window.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        // desce o ganho a tempo de cada pico sair da linha de atraso
+        let mut attack_step: f32 = 0.0;
+        for &(index, gain) in &self.gain_window {
+            if gain < self.limiter_gain {
+                let remaining = (index + self.lookahead - self.sample_index).max(1) as f32;
+                attack_step = attack_step.max((self.limiter_gain - gain) / remaining);
+            }
+        }
+
+        if attack_step > 0.0 {
+            self.limiter_gain -= attack_step;
+        } else {
+            let target = self.gain_window.front().map_or(1.0, |&(_, gain)| gain);
+            self.limiter_gain = target + (self.limiter_gain - target) * self.limiter_release;
+        }
+
+        self.sample_index += 1;
+        self.delay.push_back(input);
+        let delayed = if self.delay.len() as u64 > self.lookahead {
+            self.delay.pop_front().unwrap_or(0.0)
+        } else {
+            0.0
+        };
+
+        (delayed * self.limiter_gain).clamp(-MASTER_CEILING, MASTER_CEILING)
+    }
+
+    fn update_meter(&mut self, output: f32) {
+        self.block_peak = self.block_peak.max(output.abs());
+        self.block_sum_squares += output * output;
+        self.block_len += 1;
+
+        if self.block_len < METER_BLOCK_SIZE {
+            return;
+        }
+
+        let rms = (self.block_sum_squares / self.block_len as f32).sqrt();
+        {
+            let mut state = self.state.lock().unwrap();
+            state.levels.peak = self.block_peak.max(state.levels.peak * METER_PEAK_DECAY);
+            state.levels.rms = rms;
+            state.levels.gain_reduction = self.limiter_gain;
+            self.compressor_enabled = state.compressor_enabled;
+        }
+
+        self.block_peak = 0.0;
+        self.block_sum_squares = 0.0;
+        self.block_len = 0;
+    }
+}
+
+impl Clone for MasterBus {
+    fn clone(&self) -> Self {
+        // estado de processamento não é compartilhado, apenas controles e medidor
+        Self::with_state(self.state.clone(), self.sample_rate)
+    }
+}
+
+pub fn linear_to_db(value: f32) -> f32 {
+    20.0 * value.max(1e-6).log10()
+}
+
+pub fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn ms_to_samples(ms: f32, sample_rate: u32) -> usize {
+    (ms * sample_rate as f32 / 1000.0).round() as usize
+}
+
+fn time_coefficient(ms: f32, sample_rate: u32) -> f32 {
+    (-1.0 / (ms * 0.001 * sample_rate as f32)).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    #[test]
+    fn full_scale_burst_stays_under_ceiling() {
+        let mut bus = MasterBus::new(SAMPLE_RATE);
+
+        for i in 0..SAMPLE_RATE as usize / 10 {
+            let input = (i as f32 * 0.05).sin();
+            assert!(bus.process(input).abs() <= MASTER_CEILING);
+        }
+    }
+
+    #[test]
+    fn single_spike_is_reduced_before_it_leaves_the_delay() {
+        let mut bus = MasterBus::new(SAMPLE_RATE);
+        let lookahead = ms_to_samples(LIMITER_LOOKAHEAD_MS, SAMPLE_RATE);
+
+        bus.process(1.0);
+        for _ in 0..lookahead - 1 {
+            assert_eq!(bus.process(0.0), 0.0);
+        }
+
+        // o ganho já chegou ao necessário quando o pico sai, sem depender do corte
+        let output = bus.process(0.0);
+        assert!(output.abs() <= MASTER_CEILING);
+        assert!(bus.limiter_gain <= MASTER_CEILING + 1e-3);
+    }
+
+    #[test]
+    fn gain_recovers_after_release() {
+        let mut bus = MasterBus::new(SAMPLE_RATE);
+        for _ in 0..SAMPLE_RATE as usize / 100 {
+            bus.process(1.0);
+        }
+        assert!(bus.limiter_gain < 0.9);
+
+        let lookahead = ms_to_samples(LIMITER_LOOKAHEAD_MS, SAMPLE_RATE);
+        let release = ms_to_samples(LIMITER_RELEASE_MS * 6.0, SAMPLE_RATE);
+        for _ in 0..lookahead + release {
+            bus.process(0.0);
+        }
+
+        assert!(bus.limiter_gain > 0.99);
+        assert!(bus.levels().gain_reduction > 0.99);
+    }
+}
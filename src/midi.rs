@@ -0,0 +1,159 @@
+use crate::{
+    config::{MIDI_AMPLITUDE_CC, MIDI_PITCH_BEND_RANGE},
+    pitch::nearest_note,
+};
+
+pub const NOTE_OFF: u8 = 0x80;
+pub const NOTE_ON: u8 = 0x90;
+pub const CONTROL_CHANGE: u8 = 0xB0;
+pub const PITCH_BEND: u8 = 0xE0;
+pub const CHANNEL_PRESSURE: u8 = 0xD0;
+
+const BEND_CENTER: u16 = 8192;
+
+pub type MidiMessage = [u8; 3];
+
+/// transforma frequência e amplitude contínuas em nota + pitch bend + CC de amplitude,
+/// gerando só as mensagens que mudaram desde a última atualização
+#[derive(Debug, Clone)]
+pub struct MidiVoice {
+    channel: u8,
+    note: Option<u8>,
+    bend: u16,
+    amplitude: u8,
+}
+
+impl MidiVoice {
+    pub fn new(channel: u8) -> Self {
+        MidiVoice {
+            channel: channel & 0x0F,
+            note: None,
+            bend: BEND_CENTER,
+            amplitude: 0,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        frequency: f32,
+        amplitude: f32,
+        reference_a4: f32,
+    ) -> Vec<MidiMessage> {
+        if amplitude <= 0.0 {
+            return self.release().into_iter().collect();
+        }
+
+        let (note, cents) = nearest_note(frequency, reference_a4);
+        let note = note.clamp(0, 127) as u8;
+        let bend = bend_value(cents, MIDI_PITCH_BEND_RANGE);
+        let value = (amplitude.clamp(0.0, 1.0) * 127.0).round() as u8;
+
+        let mut messages = Vec::new();
+
+        if value != self.amplitude {
+            self.amplitude = value;
+            messages.push([CONTROL_CHANGE | self.channel, MIDI_AMPLITUDE_CC, value]);
+        }
+
+        if bend != self.bend {
+            self.bend = bend;
+            messages.push([
+                PITCH_BEND | self.channel,
+                (bend & 0x7F) as u8,
+                (bend >> 7) as u8,
+            ]);
+        }
+
+        if self.note != Some(note) {
+            messages.extend(self.release());
+            self.note = Some(note);
+            messages.push([NOTE_ON | self.channel, note, value.max(1)]);
+        }
+
+        messages
+    }
+
+    /// solta a nota que estiver soando
+    pub fn release(&mut self) -> Option<MidiMessage> {
+        self.note
+            .take()
+            .map(|note| [NOTE_OFF | self.channel, note, 0])
+    }
+
+    /// RPN 0: avisa o receptor da faixa de pitch bend usada
+    pub fn bend_range_messages(&self) -> [MidiMessage; 4] {
+        rpn_messages(self.channel, 0, MIDI_PITCH_BEND_RANGE)
+    }
+}
+
+/// ajusta um RPN (parâmetro registrado) no canal
+pub fn rpn_messages(channel: u8, parameter: u8, value: u8) -> [MidiMessage; 4] {
+    let status = CONTROL_CHANGE | channel;
+    [
+        [status, 101, 0],
+        [status, 100, parameter],
+        [status, 6, value],
+        [status, 38, 0],
+    ]
+}
+
+/// valor de 14 bits do pitch bend para um desvio em cents, com a faixa em semitons
+pub fn bend_value(cents: f32, range_semitones: u8) -> u16 {
+    let range = range_semitones as f32 * 100.0;
+    let offset = (cents / range).clamp(-1.0, 1.0) * (BEND_CENTER - 1) as f32;
+    (BEND_CENTER as f32 + offset).round() as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_update_sends_amplitude_and_note_on() {
+        let mut voice = MidiVoice::new(0);
+        let messages = voice.update(440.0, 1.0, 440.0);
+        assert_eq!(
+            messages,
+            vec![[CONTROL_CHANGE, MIDI_AMPLITUDE_CC, 127], [NOTE_ON, 69, 127]]
+        );
+        // nada mudou: nada é enviado
+        assert!(voice.update(440.0, 1.0, 440.0).is_empty());
+    }
+
+    #[test]
+    fn detune_sends_pitch_bend_without_new_note() {
+        let mut voice = MidiVoice::new(2);
+        voice.update(440.0, 0.5, 440.0);
+
+        // +25 cents com faixa de 2 semitons: 8192 + 8191 / 8 = 9216
+        let messages = voice.update(440.0 * 2f32.powf(25.0 / 1200.0), 0.5, 440.0);
+        assert_eq!(messages, vec![[PITCH_BEND | 2, 0, 72]]);
+    }
+
+    #[test]
+    fn note_change_releases_previous_note() {
+        let mut voice = MidiVoice::new(0);
+        voice.update(440.0, 1.0, 440.0);
+
+        let messages = voice.update(493.88, 1.0, 440.0);
+        assert_eq!(messages, vec![[NOTE_OFF, 69, 0], [NOTE_ON, 71, 127]]);
+    }
+
+    #[test]
+    fn silence_sends_note_off_once() {
+        let mut voice = MidiVoice::new(0);
+        voice.update(440.0, 1.0, 440.0);
+
+        assert_eq!(voice.update(440.0, 0.0, 440.0), vec![[NOTE_OFF, 69, 0]]);
+        assert!(voice.update(440.0, 0.0, 440.0).is_empty());
+        assert_eq!(voice.release(), None);
+    }
+
+    #[test]
+    fn bend_value_covers_the_range() {
+        assert_eq!(bend_value(0.0, 2), BEND_CENTER);
+        assert_eq!(bend_value(200.0, 2), 16383);
+        assert_eq!(bend_value(-200.0, 2), 1);
+        assert_eq!(bend_value(1000.0, 2), 16383);
+    }
+}
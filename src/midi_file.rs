@@ -0,0 +1,167 @@
+use std::fs;
+use std::io;
+use std::time::Instant;
+
+use crate::{
+    config::{MIDI_CHANNEL, MIDI_TICKS_PER_QUARTER},
+    midi::{MidiMessage, MidiVoice},
+};
+
+/// grava as notas, o pitch bend e o CC de amplitude da performance para um Standard MIDI File
+pub struct MidiRecorder {
+    start: Instant,
+    bpm: f32,
+    /// (segundos desde o início, mensagem MIDI)
+    events: Vec<(f32, MidiMessage)>,
+    voice: MidiVoice,
+}
+
+impl MidiRecorder {
+    pub fn new(bpm: f32) -> Self {
+        MidiRecorder {
+            start: Instant::now(),
+            bpm,
+            events: Vec::new(),
+            voice: MidiVoice::new(MIDI_CHANNEL),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// registra a frequência e a amplitude atuais
+    pub fn record(&mut self, frequency: f32, amplitude: f32, reference_a4: f32) {
+        let messages = self.voice.update(frequency, amplitude, reference_a4);
+        self.push(messages);
+    }
+
+    /// solta a nota que estiver soando
+    pub fn release(&mut self) {
+        let message = self.voice.release();
+        self.push(message);
+    }
+
+    fn push(&mut self, messages: impl IntoIterator<Item = MidiMessage>) {
+        let time = self.start.elapsed().as_secs_f32();
+        self.events
+            .extend(messages.into_iter().map(|message| (time, message)));
+    }
+
+    /// escreve a gravação como SMF formato 0 e recomeça do zero
+    pub fn write(&mut self, path: &str, bpm: f32) -> io::Result<()> {
+        self.release();
+        fs::write(path, self.encode())?;
+        *self = MidiRecorder::new(bpm);
+        Ok(())
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let ticks_per_second = self.bpm / 60.0 * MIDI_TICKS_PER_QUARTER as f32;
+        let tempo = (60_000_000.0 / self.bpm) as u32;
+
+        let mut track = Vec::new();
+
+        // tempo e faixa do pitch bend no início da trilha
+        track.extend_from_slice(&[0x00, 0xFF, 0x51, 0x03]);
+        track.extend_from_slice(&tempo.to_be_bytes()[1..]);
+        for message in self.voice.bend_range_messages() {
+            track.push(0x00);
+            track.extend_from_slice(&message);
+        }
+
+        let mut last_tick = 0;
+        for (time, message) in &self.events {
+            let tick = (time * ticks_per_second) as u32;
+            write_variable_length(&mut track, tick.saturating_sub(last_tick));
+            track.extend_from_slice(message);
+            last_tick = tick.max(last_tick);
+        }
+        track.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+
+        let mut file = Vec::new();
+        file.extend_from_slice(b"MThd");
+        file.extend_from_slice(&6u32.to_be_bytes());
+        file.extend_from_slice(&0u16.to_be_bytes()); // formato 0
+        file.extend_from_slice(&1u16.to_be_bytes()); // uma trilha
+        file.extend_from_slice(&MIDI_TICKS_PER_QUARTER.to_be_bytes());
+        file.extend_from_slice(b"MTrk");
+        file.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        file.extend_from_slice(&track);
+        file
+    }
+}
+
+fn write_variable_length(buffer: &mut Vec<u8>, mut value: u32) {
+    let mut bytes = vec![(value & 0x7F) as u8];
+    value >>= 7;
+    while value > 0 {
+        bytes.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.reverse();
+    buffer.extend_from_slice(&bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// cabeçalho MThd + início do MTrk
+    const HEADER_LENGTH: usize = 22;
+    /// tempo (7 bytes) + quatro mensagens de faixa do pitch bend com delta zero
+    const TRACK_PREFIX_LENGTH: usize = 7 + 4 * 4;
+
+    fn variable_length(value: u32) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        write_variable_length(&mut buffer, value);
+        buffer
+    }
+
+    #[test]
+    fn variable_length_quantities_match_the_spec() {
+        assert_eq!(variable_length(0), vec![0x00]);
+        assert_eq!(variable_length(0x7F), vec![0x7F]);
+        assert_eq!(variable_length(0x80), vec![0x81, 0x00]);
+        assert_eq!(variable_length(0x3FFF), vec![0xFF, 0x7F]);
+        assert_eq!(variable_length(0x0FFF_FFFF), vec![0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn header_and_track_lengths() {
+        let bytes = MidiRecorder::new(120.0).encode();
+
+        assert_eq!(&bytes[0..4], b"MThd");
+        assert_eq!(&bytes[4..8], &[0, 0, 0, 6]);
+        assert_eq!(&bytes[8..10], &[0, 0]);
+        assert_eq!(&bytes[10..12], &[0, 1]);
+        assert_eq!(&bytes[12..14], &MIDI_TICKS_PER_QUARTER.to_be_bytes());
+        assert_eq!(&bytes[14..18], b"MTrk");
+
+        let track_length = u32::from_be_bytes(bytes[18..22].try_into().unwrap()) as usize;
+        assert_eq!(track_length, bytes.len() - HEADER_LENGTH);
+    }
+
+    #[test]
+    fn track_starts_with_tempo_and_ends_with_end_of_track() {
+        let bytes = MidiRecorder::new(120.0).encode();
+        let track = &bytes[HEADER_LENGTH..];
+
+        // 500000 µs por semínima a 120 BPM
+        assert_eq!(&track[..7], &[0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20]);
+        assert_eq!(&track[track.len() - 4..], &[0x00, 0xFF, 0x2F, 0x00]);
+        assert_eq!(track.len(), TRACK_PREFIX_LENGTH + 4);
+    }
+
+    #[test]
+    fn events_are_written_with_tick_deltas() {
+        let mut recorder = MidiRecorder::new(120.0);
+        recorder.events = vec![(0.0, [0x90, 60, 100]), (1.0, [0x80, 60, 0])];
+
+        let bytes = recorder.encode();
+        let events = &bytes[HEADER_LENGTH + TRACK_PREFIX_LENGTH..bytes.len() - 4];
+
+        // um segundo a 120 BPM são duas semínimas: 960 ticks
+        assert_eq!(events, &[0x00, 0x90, 60, 100, 0x87, 0x40, 0x80, 60, 0]);
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+#[cfg(test)]
+use std::collections::VecDeque;
+use std::error::Error;
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+
+#[cfg(test)]
+use crate::midi_out::MidiOutput;
+use crate::{
+    command::Command,
+    config::{
+        CLOCK_MAX_BPM, CLOCK_MIN_BPM, MIDI_INPUT_CC_MAP, MIDI_INPUT_PORT_NAME, PITCH_LOWEST_NOTE,
+    },
+    formant::FormantAxis,
+    harmonizer::HarmonyInterval,
+    midi::{CONTROL_CHANGE, NOTE_OFF, NOTE_ON},
+    midi_out::MidiBackend,
+    pitch::Scale,
+};
+
+/// origem das mensagens MIDI recebidas
+pub trait MidiInput {
+    /// mensagens que chegaram desde a última chamada, sem bloquear
+    fn receive(&mut self) -> Result<Vec<Vec<u8>>, Box<dyn Error>>;
+
+    fn name(&self) -> String;
+}
+
+pub fn open_input(backend: MidiBackend) -> Result<Box<dyn MidiInput>, Box<dyn Error>> {
+    match backend {
+        MidiBackend::Alsa => open_alsa(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn open_alsa() -> Result<Box<dyn MidiInput>, Box<dyn Error>> {
+    Ok(Box::new(alsa_port::AlsaInput::open(MIDI_INPUT_PORT_NAME)?))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_alsa() -> Result<Box<dyn MidiInput>, Box<dyn Error>> {
+    Err("sequenciador ALSA disponível apenas no Linux".into())
+}
+
+/// entrada em memória: o que for enviado a ela (como saída) volta no próximo receive.
+/// Clones compartilham a mesma fila, então um clone serve de teclado nos testes
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct LoopbackInput {
+    queue: Arc<Mutex<VecDeque<Vec<u8>>>>,
+}
+
+#[cfg(test)]
+impl LoopbackInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(test)]
+impl MidiOutput for LoopbackInput {
+    fn send(&mut self, message: &[u8]) -> Result<(), Box<dyn Error>> {
+        let mut queue = self.queue.lock().unwrap();
+        queue.push_back(message.to_vec());
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        "loopback".to_string()
+    }
+}
+
+#[cfg(test)]
+impl MidiInput for LoopbackInput {
+    fn receive(&mut self) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        let mut queue = self.queue.lock().unwrap();
+        Ok(queue.drain(..).collect())
+    }
+
+    fn name(&self) -> String {
+        "loopback".to_string()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod alsa_port {
+    use std::error::Error;
+    use std::ffi::CString;
+
+    use alsa::seq::{MidiEvent, PortCap, PortType, Seq};
+
+    use super::MidiInput;
+
+    pub struct AlsaInput {
+        seq: Seq,
+        decoder: MidiEvent,
+        name: String,
+    }
+
+    impl AlsaInput {
+        pub fn open(name: &str) -> Result<Self, Box<dyn Error>> {
+            let seq = Seq::open(None, Some(alsa::Direction::Capture), true)?;
+            let c_name = CString::new(name)?;
+            seq.set_client_name(&c_name)?;
+
+            let port = seq.create_simple_port(
+                &c_name,
+                PortCap::WRITE | PortCap::SUBS_WRITE,
+                PortType::MIDI_GENERIC | PortType::APPLICATION,
+            )?;
+
+            let decoder = MidiEvent::new(16)?;
+            // cada mensagem decodificada vem com o byte de status
+            decoder.enable_running_status(false);
+
+            Ok(AlsaInput {
+                name: format!("ALSA {}:{} ({})", seq.client_id()?, port, name),
+                seq,
+                decoder,
+            })
+        }
+    }
+
+    impl MidiInput for AlsaInput {
+        fn receive(&mut self) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+            let mut messages = Vec::new();
+            let mut input = self.seq.input();
+
+            while input.event_input_pending(true)? > 0 {
+                let mut event = input.event_input()?;
+                let mut buffer = [0u8; 16];
+                // eventos sem equivalente MIDI (avisos de conexão etc.) não decodificam
+                if let Ok(size) = self.decoder.decode(&mut buffer, &mut event)
+                    && size > 0
+                {
+                    messages.push(buffer[..size].to_vec());
+                }
+            }
+            Ok(messages)
+        }
+
+        fn name(&self) -> String {
+            self.name.clone()
+        }
+    }
+}
+
+/// parâmetro controlado por um CC da entrada MIDI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CcTarget {
+    /// de CLOCK_MIN_BPM a CLOCK_MAX_BPM
+    Tempo,
+    /// liga a partir de 64
+    Compressor,
+    /// a faixa do controle é dividida entre os intervalos
+    Harmony,
+    Formant,
+}
+
+impl CcTarget {
+    fn command(&self, value: u8) -> Command {
+        let t = value as f32 / 127.0;
+        match self {
+            CcTarget::Tempo => {
+                Command::SetTempo((CLOCK_MIN_BPM + t * (CLOCK_MAX_BPM - CLOCK_MIN_BPM)).round())
+            }
+            CcTarget::Compressor => Command::SetCompressor(value >= 64),
+            CcTarget::Harmony => Command::SetHarmony(pick(HarmonyInterval::ALL, value)),
+            CcTarget::Formant => Command::SetFormant(pick(FormantAxis::ALL, value)),
+        }
+    }
+}
+
+/// opção da lista correspondente ao valor de 0 a 127
+fn pick<T: Copy>(options: &[T], value: u8) -> T {
+    options[(value as usize * options.len() / 128).min(options.len() - 1)]
+}
+
+/// teclado MIDI como controle: a nota mais grave segurada vira a tônica, acordes de três
+/// ou mais notas escolhem a escala e os CCs de MIDI_INPUT_CC_MAP ajustam os efeitos
+pub struct MidiControl {
+    input: Box<dyn MidiInput>,
+    held: Vec<u8>,
+    /// último comando de cada CC, para um botão girando não repetir o mesmo ajuste
+    cc_commands: HashMap<u8, Command>,
+}
+
+impl MidiControl {
+    pub fn new(input: Box<dyn MidiInput>) -> Self {
+        MidiControl {
+            input,
+            held: Vec::new(),
+            cc_commands: HashMap::new(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.input.name()
+    }
+
+    pub fn poll(&mut self) -> Result<Vec<Command>, Box<dyn Error>> {
+        let mut commands = Vec::new();
+        for message in self.input.receive()? {
+            commands.extend(self.handle(&message));
+        }
+        Ok(commands)
+    }
+
+    /// aceita mensagens de qualquer canal
+    fn handle(&mut self, message: &[u8]) -> Vec<Command> {
+        let [status, data1, data2] = match message {
+            [status, data1, data2, ..] => [*status, *data1, *data2],
+            _ => return Vec::new(),
+        };
+
+        match status & 0xF0 {
+            NOTE_ON if data2 > 0 => {
+                if !self.held.contains(&data1) {
+                    self.held.push(data1);
+                }
+                self.held_changed()
+            }
+            NOTE_ON | NOTE_OFF => {
+                self.held.retain(|&note| note != data1);
+                Vec::new()
+            }
+            CONTROL_CHANGE => {
+                let Some(&(_, target)) = MIDI_INPUT_CC_MAP.iter().find(|(cc, _)| *cc == data1)
+                else {
+                    return Vec::new();
+                };
+
+                let command = target.command(data2);
+                if self.cc_commands.insert(data1, command) == Some(command) {
+                    return Vec::new();
+                }
+                vec![command]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// sem guardar a última tônica ou escala: o teclado do computador e outros controles
+    /// podem ter mudado o instrumento desde a última nota
+    fn held_changed(&self) -> Vec<Command> {
+        let Some(&lowest) = self.held.iter().min() else {
+            return Vec::new();
+        };
+
+        // tônica como transposição a partir da nota base, na oitava mais próxima
+        let mut transpose = (lowest as i32 - PITCH_LOWEST_NOTE).rem_euclid(12);
+        if transpose > 6 {
+            transpose -= 12;
+        }
+        let mut commands = vec![Command::SetTranspose(transpose)];
+
+        if self.held.len() >= 3 {
+            match chord_scale(&self.held, lowest) {
+                Some(scale) => commands.push(Command::SetScale(scale)),
+                None => log!("[INFO] Acorde MIDI sem escala correspondente"),
+            }
+        }
+
+        commands
+    }
+}
+
+/// primeira escala (as pentatônicas vêm antes) que contém todas as notas do acorde
+fn chord_scale(notes: &[u8], root: u8) -> Option<Scale> {
+    Scale::ALL.iter().copied().find(|scale| {
+        notes
+            .iter()
+            .all(|&note| scale.intervals().contains(&((note - root) as i32 % 12)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn control() -> (MidiControl, LoopbackInput) {
+        let keyboard = LoopbackInput::new();
+        (MidiControl::new(Box::new(keyboard.clone())), keyboard)
+    }
+
+    fn play(keyboard: &mut LoopbackInput, messages: &[[u8; 3]]) {
+        for message in messages {
+            keyboard.send(message).unwrap();
+        }
+    }
+
+    #[test]
+    fn lowest_note_sets_transpose_in_nearest_octave() {
+        let (mut control, mut keyboard) = control();
+
+        play(&mut keyboard, &[[NOTE_ON, 50, 100], [NOTE_OFF, 50, 0]]);
+        assert_eq!(control.poll().unwrap(), vec![Command::SetTranspose(2)]);
+
+        // sol fica mais perto descendo: -5 em vez de +7; aceita qualquer canal
+        play(&mut keyboard, &[[NOTE_ON | 3, 67, 100]]);
+        assert_eq!(control.poll().unwrap(), vec![Command::SetTranspose(-5)]);
+    }
+
+    #[test]
+    fn same_note_again_repeats_the_command() {
+        let (mut control, mut keyboard) = control();
+
+        // nota ligada com velocidade 0 também solta a tecla
+        play(&mut keyboard, &[[NOTE_ON, 50, 100], [NOTE_ON, 50, 0]]);
+        control.poll().unwrap();
+
+        // o controlador pode ter sido transposto por outra fonte nesse meio tempo
+        play(&mut keyboard, &[[NOTE_ON, 50, 100]]);
+        assert_eq!(control.poll().unwrap(), vec![Command::SetTranspose(2)]);
+    }
+
+    #[test]
+    fn chords_choose_the_scale() {
+        let (mut control, mut keyboard) = control();
+
+        play(&mut keyboard, &[[NOTE_ON, 57, 90], [NOTE_ON, 60, 90]]);
+        control.poll().unwrap();
+        play(&mut keyboard, &[[NOTE_ON, 64, 90]]);
+        assert_eq!(
+            control.poll().unwrap(),
+            vec![
+                Command::SetTranspose(-3),
+                Command::SetScale(Scale::PentatonicMinor)
+            ]
+        );
+
+        // C E G B: a sétima maior só cabe na escala maior
+        play(
+            &mut keyboard,
+            &[
+                [NOTE_OFF, 57, 0],
+                [NOTE_OFF, 60, 0],
+                [NOTE_OFF, 64, 0],
+                [NOTE_ON, 48, 90],
+                [NOTE_ON, 52, 90],
+                [NOTE_ON, 55, 90],
+                [NOTE_ON, 59, 90],
+            ],
+        );
+        let commands = control.poll().unwrap();
+        assert_eq!(commands.last(), Some(&Command::SetScale(Scale::Major)));
+    }
+
+    #[test]
+    fn mapped_ccs_send_commands_only_when_they_change() {
+        let (mut control, mut keyboard) = control();
+
+        play(
+            &mut keyboard,
+            &[
+                [CONTROL_CHANGE, 20, 0],
+                [CONTROL_CHANGE, 20, 0],
+                [CONTROL_CHANGE, 20, 127],
+                [CONTROL_CHANGE, 21, 100],
+                [CONTROL_CHANGE, 22, 0],
+                [CONTROL_CHANGE, 7, 100],
+            ],
+        );
+        assert_eq!(
+            control.poll().unwrap(),
+            vec![
+                Command::SetTempo(CLOCK_MIN_BPM),
+                Command::SetTempo(CLOCK_MAX_BPM),
+                Command::SetCompressor(true),
+                Command::SetHarmony(HarmonyInterval::Off),
+            ]
+        );
+    }
+
+    #[test]
+    fn short_messages_are_ignored() {
+        let (mut control, mut keyboard) = control();
+        keyboard.send(&[0xF8]).unwrap();
+        keyboard.send(&[NOTE_ON, 60]).unwrap();
+        assert!(control.poll().unwrap().is_empty());
+    }
+}
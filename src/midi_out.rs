@@ -0,0 +1,203 @@
+use std::error::Error;
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    config::{MIDI_CHANNEL, MIDI_PORT_NAME},
+    midi::{MidiMessage, MidiVoice},
+};
+
+/// destino das mensagens MIDI ao vivo
+pub trait MidiOutput {
+    fn send(&mut self, message: &[u8]) -> Result<(), Box<dyn Error>>;
+
+    fn name(&self) -> String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MidiBackend {
+    /// porta virtual do sequenciador ALSA, visível para qualquer sintetizador
+    Alsa,
+}
+
+pub fn open_output(backend: MidiBackend) -> Result<Box<dyn MidiOutput>, Box<dyn Error>> {
+    match backend {
+        MidiBackend::Alsa => open_alsa(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn open_alsa() -> Result<Box<dyn MidiOutput>, Box<dyn Error>> {
+    Ok(Box::new(alsa_port::AlsaOutput::open(MIDI_PORT_NAME)?))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_alsa() -> Result<Box<dyn MidiOutput>, Box<dyn Error>> {
+    Err("sequenciador ALSA disponível apenas no Linux".into())
+}
+
+/// saída em memória para os testes: clones compartilham a mesma lista de mensagens
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct LoopbackOutput {
+    messages: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+#[cfg(test)]
+impl LoopbackOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn messages(&self) -> Vec<Vec<u8>> {
+        let messages = self.messages.lock().unwrap();
+        messages.clone()
+    }
+}
+
+#[cfg(test)]
+impl MidiOutput for LoopbackOutput {
+    fn send(&mut self, message: &[u8]) -> Result<(), Box<dyn Error>> {
+        let mut messages = self.messages.lock().unwrap();
+        messages.push(message.to_vec());
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("loopback ({} mensagens)", self.messages().len())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod alsa_port {
+    use std::error::Error;
+    use std::ffi::CString;
+
+    use alsa::seq::{MidiEvent, PortCap, PortType, Seq};
+
+    use super::MidiOutput;
+
+    pub struct AlsaOutput {
+        seq: Seq,
+        port: i32,
+        encoder: MidiEvent,
+        name: String,
+    }
+
+    impl AlsaOutput {
+        pub fn open(name: &str) -> Result<Self, Box<dyn Error>> {
+            let seq = Seq::open(None, Some(alsa::Direction::Playback), true)?;
+            let c_name = CString::new(name)?;
+            seq.set_client_name(&c_name)?;
+
+            let port = seq.create_simple_port(
+                &c_name,
+                PortCap::READ | PortCap::SUBS_READ,
+                PortType::MIDI_GENERIC | PortType::APPLICATION,
+            )?;
+
+            Ok(AlsaOutput {
+                name: format!("ALSA {}:{} ({})", seq.client_id()?, port, name),
+                seq,
+                port,
+                encoder: MidiEvent::new(16)?,
+            })
+        }
+    }
+
+    impl MidiOutput for AlsaOutput {
+        fn send(&mut self, message: &[u8]) -> Result<(), Box<dyn Error>> {
+            // sem running status: cada mensagem é codificada inteira
+            self.encoder.enable_running_status(false);
+            let (_, event) = self.encoder.encode(message)?;
+
+            if let Some(mut event) = event {
+                event.set_source(self.port);
+                event.set_subs();
+                event.set_direct();
+                self.seq.event_output_direct(&mut event)?;
+            }
+            Ok(())
+        }
+
+        fn name(&self) -> String {
+            self.name.clone()
+        }
+    }
+}
+
+/// envia a voz principal como MIDI ao vivo
+pub struct LiveMidi {
+    output: Box<dyn MidiOutput>,
+    voice: MidiVoice,
+}
+
+impl LiveMidi {
+    pub fn new(mut output: Box<dyn MidiOutput>) -> Result<Self, Box<dyn Error>> {
+        let voice = MidiVoice::new(MIDI_CHANNEL);
+        for message in voice.bend_range_messages() {
+            output.send(&message)?;
+        }
+        Ok(LiveMidi { output, voice })
+    }
+
+    pub fn update(
+        &mut self,
+        frequency: f32,
+        amplitude: f32,
+        reference_a4: f32,
+    ) -> Result<(), Box<dyn Error>> {
+        let messages = self.voice.update(frequency, amplitude, reference_a4);
+        self.send_all(messages)
+    }
+
+    pub fn release(&mut self) -> Result<(), Box<dyn Error>> {
+        let message = self.voice.release();
+        self.send_all(message)
+    }
+
+    pub fn name(&self) -> String {
+        self.output.name()
+    }
+
+    fn send_all(
+        &mut self,
+        messages: impl IntoIterator<Item = MidiMessage>,
+    ) -> Result<(), Box<dyn Error>> {
+        for message in messages {
+            self.output.send(&message)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{MIDI_AMPLITUDE_CC, MIDI_PITCH_BEND_RANGE},
+        midi::{CONTROL_CHANGE, NOTE_OFF, NOTE_ON},
+    };
+
+    #[test]
+    fn live_midi_sends_bend_range_then_notes() {
+        let loopback = LoopbackOutput::new();
+        let mut live = LiveMidi::new(Box::new(loopback.clone())).unwrap();
+        live.update(440.0, 1.0, 440.0).unwrap();
+        live.release().unwrap();
+
+        let status = CONTROL_CHANGE | MIDI_CHANNEL;
+        assert_eq!(
+            loopback.messages(),
+            vec![
+                vec![status, 101, 0],
+                vec![status, 100, 0],
+                vec![status, 6, MIDI_PITCH_BEND_RANGE],
+                vec![status, 38, 0],
+                vec![status, MIDI_AMPLITUDE_CC, 127],
+                vec![NOTE_ON | MIDI_CHANNEL, 69, 127],
+                vec![NOTE_OFF | MIDI_CHANNEL, 69, 0],
+            ]
+        );
+    }
+}
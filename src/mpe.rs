@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::{
+    config::{MPE_MAX_MARKER_SIZE, MPE_MEMBER_CHANNELS, MPE_PITCH_BEND_RANGE, MPE_SLIDE_CC},
+    midi::{
+        CHANNEL_PRESSURE, CONTROL_CHANGE, NOTE_OFF, NOTE_ON, PITCH_BEND, bend_value, rpn_messages,
+    },
+    midi_out::MidiOutput,
+    pitch::{PitchMapping, frequency_to_midi},
+    ui::NormalizedPosition,
+};
+
+/// canal de gerência da zona inferior do MPE (canal 1)
+const MANAGER_CHANNEL: u8 = 0;
+
+/// RPN 6: mensagem de configuração do MPE
+const MPE_CONFIGURATION_RPN: u8 = 6;
+
+/// nota de um marcador, presa ao seu canal-membro
+struct MpeNote {
+    channel: u8,
+    note: u8,
+    bend: u16,
+    slide: u8,
+    pressure: u8,
+}
+
+/// saída MPE: cada marcador ganha um canal-membro com pitch bend, slide e pressão próprios.
+/// Y dá a altura (nota + pitch bend por nota), X o slide (CC74) e o tamanho a pressão
+pub struct MpeOutput {
+    output: Box<dyn MidiOutput>,
+    notes: HashMap<i32, MpeNote>,
+}
+
+impl MpeOutput {
+    pub fn new(mut output: Box<dyn MidiOutput>) -> Result<Self, Box<dyn Error>> {
+        for message in rpn_messages(MANAGER_CHANNEL, MPE_CONFIGURATION_RPN, MPE_MEMBER_CHANNELS) {
+            output.send(&message)?;
+        }
+        for channel in member_channels() {
+            for message in rpn_messages(channel, 0, MPE_PITCH_BEND_RANGE) {
+                output.send(&message)?;
+            }
+        }
+
+        Ok(MpeOutput {
+            output,
+            notes: HashMap::new(),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.output.name()
+    }
+
+    pub fn active_notes(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// atualiza as notas a partir dos marcadores visíveis (id, posição) deste quadro
+    pub fn update(
+        &mut self,
+        markers: &[(i32, NormalizedPosition)],
+        mapping: &PitchMapping,
+    ) -> Result<(), Box<dyn Error>> {
+        // marcadores que sumiram soltam a nota e liberam o canal
+        let gone: Vec<i32> = self
+            .notes
+            .keys()
+            .filter(|id| !markers.iter().any(|(marker_id, _)| marker_id == *id))
+            .copied()
+            .collect();
+        for id in gone {
+            self.note_off(id)?;
+        }
+
+        for (id, position) in markers {
+            let midi = frequency_to_midi(
+                mapping.frequency_for_position(position.y),
+                mapping.reference_a4(),
+            );
+            let slide = to_7bit((position.x + 1.0) / 2.0);
+            let pressure = to_7bit(position.size / MPE_MAX_MARKER_SIZE);
+
+            let Some(note) = self.notes.get(id) else {
+                // sem canal livre o marcador fica sem nota até algum outro sumir
+                if let Some(channel) = self.free_channel() {
+                    self.note_on(*id, channel, midi, slide, pressure)?;
+                }
+                continue;
+            };
+
+            // a nota fica fixa desde o note on; a altura segue só pelo pitch bend
+            let channel = note.channel;
+            let bend = bend_value((midi - note.note as f32) * 100.0, MPE_PITCH_BEND_RANGE);
+
+            let mut messages = Vec::new();
+            if bend != note.bend {
+                messages.push(vec![
+                    PITCH_BEND | channel,
+                    (bend & 0x7F) as u8,
+                    (bend >> 7) as u8,
+                ]);
+            }
+            if slide != note.slide {
+                messages.push(vec![CONTROL_CHANGE | channel, MPE_SLIDE_CC, slide]);
+            }
+            if pressure != note.pressure {
+                messages.push(vec![CHANNEL_PRESSURE | channel, pressure]);
+            }
+
+            for message in messages {
+                self.send(&message)?;
+            }
+
+            if let Some(note) = self.notes.get_mut(id) {
+                note.bend = bend;
+                note.slide = slide;
+                note.pressure = pressure;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// solta todas as notas
+    pub fn release_all(&mut self) -> Result<(), Box<dyn Error>> {
+        let ids: Vec<i32> = self.notes.keys().copied().collect();
+        for id in ids {
+            self.note_off(id)?;
+        }
+        Ok(())
+    }
+
+    fn note_on(
+        &mut self,
+        id: i32,
+        channel: u8,
+        midi: f32,
+        slide: u8,
+        pressure: u8,
+    ) -> Result<(), Box<dyn Error>> {
+        let note = midi.round().clamp(0.0, 127.0) as u8;
+        let bend = bend_value((midi - note as f32) * 100.0, MPE_PITCH_BEND_RANGE);
+
+        // a expressão vai antes da nota para o sintetizador já começar no lugar certo
+        self.send(&[PITCH_BEND | channel, (bend & 0x7F) as u8, (bend >> 7) as u8])?;
+        self.send(&[CONTROL_CHANGE | channel, MPE_SLIDE_CC, slide])?;
+        self.send(&[CHANNEL_PRESSURE | channel, pressure])?;
+        self.send(&[NOTE_ON | channel, note, pressure.max(1)])?;
+
+        self.notes.insert(
+            id,
+            MpeNote {
+                channel,
+                note,
+                bend,
+                slide,
+                pressure,
+            },
+        );
+        Ok(())
+    }
+
+    fn note_off(&mut self, id: i32) -> Result<(), Box<dyn Error>> {
+        if let Some(note) = self.notes.remove(&id) {
+            self.send(&[NOTE_OFF | note.channel, note.note, 0])?;
+        }
+        Ok(())
+    }
+
+    fn free_channel(&self) -> Option<u8> {
+        member_channels().find(|channel| self.notes.values().all(|note| note.channel != *channel))
+    }
+
+    fn send(&mut self, message: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.output.send(message)
+    }
+}
+
+/// canais-membro da zona inferior, logo depois do canal de gerência
+fn member_channels() -> impl Iterator<Item = u8> {
+    MANAGER_CHANNEL + 1..=MANAGER_CHANNEL + MPE_MEMBER_CHANNELS
+}
+
+fn to_7bit(t: f32) -> u8 {
+    (t.clamp(0.0, 1.0) * 127.0).round() as u8
+}
@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    config::{NOISE_MIX, NOISE_SYNC_SAMPLES},
+    rng::Rng,
+};
+
+/// cor do ruído, da mais brilhante para a mais escura
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseColor {
+    White,
+    Pink,
+    Brown,
+}
+
+impl NoiseColor {
+    /// posição da cor na escala contínua usada quando o X controla a cor
+    fn position(&self) -> f32 {
+        match self {
+            NoiseColor::White => 0.0,
+            NoiseColor::Pink => 0.5,
+            NoiseColor::Brown => 1.0,
+        }
+    }
+}
+
+/// o que o eixo X controla nas vozes de ruído
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseXControl {
+    /// mistura entre o oscilador e o ruído
+    Level,
+    /// passa continuamente de branco para rosa e marrom
+    Color,
+}
+
+impl NoiseXControl {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoiseXControl::Level => "Mistura",
+            NoiseXControl::Color => "Cor",
+        }
+    }
+}
+
+/// ruído branco, rosa e marrom misturado ao oscilador senoidal
+pub struct NoiseVoice {
+    x_control: Arc<Mutex<NoiseXControl>>,
+    /// cópia de `x_control` da thread de áudio, relida a cada NOISE_SYNC_SAMPLES
+    current_x_control: NoiseXControl,
+    sync_counter: usize,
+    rng: Rng,
+    /// filtros do ruído rosa (aproximação de Paul Kellet)
+    pink: [f32; 3],
+    brown: f32,
+}
+
+impl NoiseVoice {
+    pub fn new() -> Self {
+        Self::with_x_control(Arc::new(Mutex::new(NoiseXControl::Level)))
+    }
+
+    fn with_x_control(x_control: Arc<Mutex<NoiseXControl>>) -> Self {
+        let current_x_control = *x_control.lock().unwrap();
+        NoiseVoice {
+            x_control,
+            current_x_control,
+            sync_counter: 0,
+            rng: Rng::new(),
+            pink: [0.0; 3],
+            brown: 0.0,
+        }
+    }
+
+    pub fn set_x_control(&self, control: NoiseXControl) {
+        let mut x_control = self.x_control.lock().unwrap();
+        *x_control = control;
+    }
+
+    pub fn get_x_control(&self) -> NoiseXControl {
+        let x_control = self.x_control.lock().unwrap();
+        *x_control
+    }
+
+    /// mistura `oscillator` com o ruído; X em [-1, 1] escolhe a mistura ou a cor
+    pub fn next_sample(&mut self, oscillator: f32, color: NoiseColor, x: f32) -> f32 {
+        let t = ((x + 1.0) / 2.0).clamp(0.0, 1.0);
+        if self.sync_counter == 0 {
+            self.current_x_control = self.get_x_control();
+        }
+        self.sync_counter = (self.sync_counter + 1) % NOISE_SYNC_SAMPLES;
+
+        let (mix, color) = match self.current_x_control {
+            NoiseXControl::Level => (t, color.position()),
+            NoiseXControl::Color => (NOISE_MIX, t),
+        };
+
+        let noise = self.next_noise(color);
+        oscillator * (1.0 - mix) + noise * mix
+    }
+
+    /// `color` em [0, 1]: 0 branco, 0.5 rosa, 1 marrom, interpolando entre eles
+    fn next_noise(&mut self, color: f32) -> f32 {
+        let white = self.rng.next_bipolar();
+
+        // os filtros rodam sempre para não haver saltos ao trocar de cor
+        self.pink[0] = 0.99765 * self.pink[0] + white * 0.0990460;
+        self.pink[1] = 0.96300 * self.pink[1] + white * 0.2965164;
+        self.pink[2] = 0.57000 * self.pink[2] + white * 1.0526913;
+        let pink = (self.pink.iter().sum::<f32>() + white * 0.1848) * 0.25;
+
+        self.brown = (self.brown + white * 0.02) / 1.02;
+        let brown = self.brown * 3.5;
+
+        if color < 0.5 {
+            let t = color * 2.0;
+            white * (1.0 - t) + pink * t
+        } else {
+            let t = (color - 0.5) * 2.0;
+            pink * (1.0 - t) + brown * t
+        }
+    }
+}
+
+impl Clone for NoiseVoice {
+    fn clone(&self) -> Self {
+        // o estado dos filtros não é compartilhado
+        Self::with_x_control(self.x_control.clone())
+    }
+}
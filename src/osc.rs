@@ -0,0 +1,513 @@
+use std::error::Error;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::{
+    command::{Command, scale_by_name, voice_by_name},
+    config::PITCH_TRANSPOSE_LIMIT,
+    formant::FormantAxis,
+    harmonizer::HarmonyInterval,
+    looper::LooperCommand,
+    pitch::Scale,
+    theremin::{ThereminController, VoiceType},
+    ui::{DetectedMarker, NormalizedPosition},
+};
+
+/// argumento de uma mensagem OSC
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+}
+
+impl OscArg {
+    /// valor numérico, aceitando int ou float (controles de tablet costumam mandar float)
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            OscArg::Int(value) => Some(*value as f32),
+            OscArg::Float(value) => Some(*value),
+            OscArg::Str(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+impl OscMessage {
+    pub fn new(address: &str, args: Vec<OscArg>) -> Self {
+        OscMessage {
+            address: address.to_string(),
+            args,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        write_string(&mut buffer, &self.address);
+
+        let mut tags = String::from(",");
+        for arg in &self.args {
+            tags.push(match arg {
+                OscArg::Int(_) => 'i',
+                OscArg::Float(_) => 'f',
+                OscArg::Str(_) => 's',
+            });
+        }
+        write_string(&mut buffer, &tags);
+
+        for arg in &self.args {
+            match arg {
+                OscArg::Int(value) => buffer.extend_from_slice(&value.to_be_bytes()),
+                OscArg::Float(value) => buffer.extend_from_slice(&value.to_be_bytes()),
+                OscArg::Str(value) => write_string(&mut buffer, value),
+            }
+        }
+        buffer
+    }
+
+    pub fn decode(packet: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut offset = 0;
+        let address = read_string(packet, &mut offset)?;
+        // mensagens antigas podem vir sem a string de tipos
+        let tags = if offset < packet.len() {
+            read_string(packet, &mut offset)?
+        } else {
+            String::from(",")
+        };
+
+        let mut args = Vec::new();
+        for tag in tags.chars().skip(1) {
+            let arg = match tag {
+                'i' => OscArg::Int(i32::from_be_bytes(read_word(packet, &mut offset)?)),
+                'f' => OscArg::Float(f32::from_be_bytes(read_word(packet, &mut offset)?)),
+                's' => OscArg::Str(read_string(packet, &mut offset)?),
+                'T' => OscArg::Int(1),
+                'F' => OscArg::Int(0),
+                _ => return Err(format!("tipo OSC não suportado: {}", tag).into()),
+            };
+            args.push(arg);
+        }
+
+        Ok(OscMessage { address, args })
+    }
+}
+
+/// mensagens de um pacote, abrindo bundles (inclusive aninhados)
+pub fn decode_packet(packet: &[u8]) -> Result<Vec<OscMessage>, Box<dyn Error>> {
+    if !packet.starts_with(b"#bundle\0") {
+        return Ok(vec![OscMessage::decode(packet)?]);
+    }
+
+    // "#bundle" + etiqueta de tempo, ignorada: tudo é executado ao chegar
+    let mut offset = 16;
+    let mut messages = Vec::new();
+    while offset < packet.len() {
+        // tamanho sem sinal: um valor negativo vira enorme e cai na checagem abaixo
+        let size = u32::from_be_bytes(read_word(packet, &mut offset)?) as usize;
+        let end = offset
+            .checked_add(size)
+            .filter(|&end| end <= packet.len())
+            .ok_or("bundle OSC truncado")?;
+        messages.extend(decode_packet(&packet[offset..end])?);
+        offset = end;
+    }
+    Ok(messages)
+}
+
+/// agrupa as mensagens num bundle com etiqueta de tempo "imediatamente"
+pub fn encode_bundle(messages: &[OscMessage]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    write_string(&mut buffer, "#bundle");
+    buffer.extend_from_slice(&1u64.to_be_bytes());
+
+    for message in messages {
+        let encoded = message.encode();
+        buffer.extend_from_slice(&(encoded.len() as i32).to_be_bytes());
+        buffer.extend_from_slice(&encoded);
+    }
+    buffer
+}
+
+/// string OSC: terminada em zero e completada até múltiplo de 4 bytes
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(value.as_bytes());
+    let padding = 4 - value.len() % 4;
+    buffer.extend(std::iter::repeat_n(0, padding));
+}
+
+fn read_string(packet: &[u8], offset: &mut usize) -> Result<String, Box<dyn Error>> {
+    let rest = packet.get(*offset..).ok_or("pacote OSC truncado")?;
+    let end = rest
+        .iter()
+        .position(|&byte| byte == 0)
+        .ok_or("string OSC sem terminador")?;
+    let value = std::str::from_utf8(&rest[..end])?.to_string();
+    *offset += (end / 4 + 1) * 4;
+    Ok(value)
+}
+
+fn read_word(packet: &[u8], offset: &mut usize) -> Result<[u8; 4], Box<dyn Error>> {
+    let word = packet
+        .get(*offset..*offset + 4)
+        .ok_or("pacote OSC truncado")?
+        .try_into()?;
+    *offset += 4;
+    Ok(word)
+}
+
+/// envia o rastreamento e o estado do sintetizador por UDP a cada quadro
+pub struct OscSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+}
+
+impl OscSender {
+    /// `target` no formato host:porta
+    pub fn open(target: &str) -> Result<Self, Box<dyn Error>> {
+        let target = target
+            .to_socket_addrs()?
+            .next()
+            .ok_or("endereço OSC sem resolução")?;
+
+        // sem connect: ninguém escutando no destino não vira erro no próximo envio
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+
+        Ok(OscSender { socket, target })
+    }
+
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// /theremin/markers (quantidade), /theremin/marker (id, x, y, rotação, tamanho),
+    /// /theremin/marker/corners (id e os quatro cantos normalizados) e
+    /// /theremin/synth (frequência, amplitude, som ligado)
+    pub fn send_frame(
+        &self,
+        markers: &[DetectedMarker],
+        positions: &[(i32, NormalizedPosition)],
+        frame_width: i32,
+        frame_height: i32,
+        controller: &ThereminController,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut messages = vec![OscMessage::new(
+            "/theremin/markers",
+            vec![OscArg::Int(markers.len() as i32)],
+        )];
+
+        for (id, position) in positions {
+            messages.push(OscMessage::new(
+                "/theremin/marker",
+                vec![
+                    OscArg::Int(*id),
+                    OscArg::Float(position.x),
+                    OscArg::Float(position.y),
+                    OscArg::Float(position.rotation),
+                    OscArg::Float(position.size),
+                ],
+            ));
+        }
+
+        for marker in markers {
+            let mut args = vec![OscArg::Int(marker.id)];
+            for corner in &marker.corners {
+                args.push(OscArg::Float(corner.x * 2.0 / frame_width as f32 - 1.0));
+                args.push(OscArg::Float(corner.y * 2.0 / frame_height as f32 - 1.0));
+            }
+            messages.push(OscMessage::new("/theremin/marker/corners", args));
+        }
+
+        messages.push(OscMessage::new(
+            "/theremin/synth",
+            vec![
+                OscArg::Float(controller.get_frequency()),
+                OscArg::Float(controller.get_amplitude()),
+                OscArg::Int(controller.is_enabled() as i32),
+            ],
+        ));
+
+        self.socket
+            .send_to(&encode_bundle(&messages), self.target)?;
+        Ok(())
+    }
+}
+
+/// recebe comandos OSC sem bloquear o laço de vídeo
+pub struct OscReceiver {
+    socket: UdpSocket,
+    address: SocketAddr,
+}
+
+impl OscReceiver {
+    pub fn bind(address: &str) -> Result<Self, Box<dyn Error>> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        let address = socket.local_addr()?;
+        Ok(OscReceiver { socket, address })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// esvazia a fila do socket e devolve os comandos reconhecidos
+    pub fn poll(&self) -> Vec<Command> {
+        let mut commands = Vec::new();
+        let mut buffer = [0u8; 1536];
+
+        loop {
+            let size = match self.socket.recv_from(&mut buffer) {
+                Ok((size, _)) => size,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("[ERROR] Erro ao receber OSC: {}", e);
+                    break;
+                }
+            };
+
+            match decode_packet(&buffer[..size]) {
+                Ok(messages) => {
+                    for message in messages {
+                        match command_for_message(&message) {
+                            Some(command) => commands.push(command),
+                            None => log!(
+                                "[INFO] Mensagem OSC ignorada: {} {:?}",
+                                message.address, message.args
+                            ),
+                        }
+                    }
+                }
+                Err(e) => eprintln!("[ERROR] Pacote OSC inválido: {}", e),
+            }
+        }
+
+        commands
+    }
+}
+
+/// endereços aceitos; sem argumento cada um faz o mesmo que a tecla correspondente
+///
+/// /theremin/sound [on], /theremin/compressor [on], /theremin/continuous [on],
+/// /theremin/voice [nome ou índice], /theremin/scale [nome ou índice],
+/// /theremin/harmony [índice], /theremin/formant [índice], /theremin/root semitons,
+/// /theremin/transpose passo, /theremin/tempo [bpm],
+/// /theremin/loop/{record,overdub,play,clear}, /theremin/quit ...
+pub fn command_for_message(message: &OscMessage) -> Option<Command> {
+    let arg = message.args.first();
+    let number = arg.and_then(OscArg::as_f32);
+    // NaN e infinito (aceitos pelo parse de f32 do texto) não viram parâmetro
+    if number.is_some_and(|value| !value.is_finite()) {
+        return None;
+    }
+    let semitones =
+        |value: f32| (value.round() as i32).clamp(-PITCH_TRANSPOSE_LIMIT, PITCH_TRANSPOSE_LIMIT);
+
+    let command = match message.address.as_str() {
+        "/theremin/quit" => Command::Quit,
+        "/theremin/sound" => match number {
+            Some(value) => Command::SetSound(value > 0.5),
+            None => Command::ToggleSound,
+        },
+        "/theremin/debug" => Command::ToggleDebug,
+        "/theremin/events" => Command::ToggleEventStream,
+        "/theremin/compressor" => match number {
+            Some(value) => Command::SetCompressor(value > 0.5),
+            None => Command::ToggleCompressor,
+        },
+        "/theremin/amplitude_curve" => Command::CycleAmplitudeCurve,
+        "/theremin/voice" => match arg {
+            Some(OscArg::Str(name)) => Command::SetVoice(voice_by_name(name)?),
+            Some(_) => Command::SetVoice(*VoiceType::ALL.get(number? as usize)?),
+            None => Command::CycleVoice,
+        },
+        "/theremin/granular_y" => Command::ToggleGranularY,
+        "/theremin/noise_x" => Command::ToggleNoiseX,
+        "/theremin/harmony" => match number {
+            Some(index) => Command::SetHarmony(*HarmonyInterval::ALL.get(index as usize)?),
+            None => Command::CycleHarmony,
+        },
+        "/theremin/formant" => match number {
+            Some(index) => Command::SetFormant(*FormantAxis::ALL.get(index as usize)?),
+            None => Command::CycleFormant,
+        },
+        "/theremin/mode" => Command::CyclePlayMode,
+        "/theremin/scale" => match arg {
+            Some(OscArg::Str(name)) => Command::SetScale(scale_by_name(name)?),
+            Some(_) => Command::SetScale(*Scale::ALL.get(number? as usize)?),
+            None => Command::CycleScale,
+        },
+        "/theremin/continuous" => match number {
+            Some(value) => Command::SetContinuousPitch(value > 0.5),
+            None => Command::ToggleContinuousPitch,
+        },
+        "/theremin/chord_type" => Command::ToggleChordType,
+        "/theremin/root" => Command::SetTranspose(semitones(number?)),
+        "/theremin/transpose" => Command::Transpose(number.map_or(1, semitones)),
+        "/theremin/tempo" => match number {
+            Some(bpm) => Command::SetTempo(bpm),
+            None => Command::ChangeTempo(1),
+        },
+        "/theremin/click" => Command::ToggleClick,
+        "/theremin/quantize" => Command::ToggleQuantize,
+        "/theremin/arp_x" => Command::ToggleArpX,
+        "/theremin/arp_pattern" => Command::CycleArpPattern,
+        "/theremin/loss_policy" => Command::CycleLossPolicy,
+        "/theremin/loop/record" => Command::Looper(LooperCommand::Record),
+        "/theremin/loop/overdub" => Command::Looper(LooperCommand::Overdub),
+        "/theremin/loop/play" => Command::Looper(LooperCommand::TogglePlay),
+        "/theremin/loop/clear" => Command::Looper(LooperCommand::Clear),
+        "/theremin/midi/export" => Command::ExportMidi,
+        "/theremin/midi/live" => Command::ToggleLiveMidi,
+        "/theremin/midi/mpe" => Command::ToggleMpe,
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// os mesmos endereços em forma de texto, para WebSocket e socket de controle:
+/// "/theremin/sound 0", "/theremin/scale Pentatonica menor", "/theremin/tempo 96"
+pub fn text_command(line: &str) -> Option<Command> {
+    let line = line.trim();
+    let (address, argument) = line.split_once(' ').unwrap_or((line, ""));
+    let argument = argument.trim();
+
+    let args = if argument.is_empty() {
+        Vec::new()
+    } else if let Ok(value) = argument.parse::<i32>() {
+        vec![OscArg::Int(value)]
+    } else if let Ok(value) = argument.parse::<f32>() {
+        vec![OscArg::Float(value)]
+    } else {
+        vec![OscArg::Str(argument.to_string())]
+    };
+
+    command_for_message(&OscMessage::new(address, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(elements: &[Vec<u8>]) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        write_string(&mut buffer, "#bundle");
+        buffer.extend_from_slice(&1u64.to_be_bytes());
+        for element in elements {
+            buffer.extend_from_slice(&(element.len() as u32).to_be_bytes());
+            buffer.extend_from_slice(element);
+        }
+        buffer
+    }
+
+    #[test]
+    fn message_decodes_known_bytes() {
+        let mut packet = b"/theremin/tempo\0,if\0".to_vec();
+        packet.extend_from_slice(&120i32.to_be_bytes());
+        packet.extend_from_slice(&0.5f32.to_be_bytes());
+
+        let message = OscMessage::decode(&packet).unwrap();
+        assert_eq!(message.address, "/theremin/tempo");
+        assert_eq!(message.args, vec![OscArg::Int(120), OscArg::Float(0.5)]);
+    }
+
+    #[test]
+    fn message_round_trips() {
+        let message = OscMessage::new(
+            "/theremin/scale",
+            vec![OscArg::Str("Maior".to_string()), OscArg::Int(-3)],
+        );
+        let encoded = message.encode();
+        assert_eq!(encoded.len() % 4, 0);
+        assert_eq!(OscMessage::decode(&encoded).unwrap(), message);
+    }
+
+    #[test]
+    fn message_accepts_missing_tags_and_booleans() {
+        let message = OscMessage::decode(b"/theremin/quit\0\0").unwrap();
+        assert!(message.args.is_empty());
+
+        let message = OscMessage::decode(b"/theremin/sound\0,TF\0").unwrap();
+        assert_eq!(message.args, vec![OscArg::Int(1), OscArg::Int(0)]);
+    }
+
+    #[test]
+    fn message_rejects_truncated_and_unknown_args() {
+        assert!(OscMessage::decode(b"/theremin/tempo\0,i\0\0\0\0").is_err());
+        assert!(OscMessage::decode(b"/theremin/tempo\0,d\0\0").is_err());
+        assert!(OscMessage::decode(b"/sem/terminador").is_err());
+    }
+
+    #[test]
+    fn nested_bundles_are_flattened_in_order() {
+        let first = OscMessage::new("/theremin/sound", vec![OscArg::Int(1)]);
+        let second = OscMessage::new("/theremin/tempo", vec![OscArg::Float(90.0)]);
+        let third = OscMessage::new("/theremin/quit", Vec::new());
+
+        let inner = encode_bundle(&[second.clone(), third.clone()]);
+        let packet = bundle(&[first.encode(), inner]);
+
+        assert_eq!(decode_packet(&packet).unwrap(), vec![first, second, third]);
+    }
+
+    #[test]
+    fn plain_message_is_a_packet_of_one() {
+        let message = OscMessage::new("/theremin/debug", Vec::new());
+        assert_eq!(decode_packet(&message.encode()).unwrap(), vec![message]);
+    }
+
+    #[test]
+    fn bundle_sizes_past_the_end_are_rejected() {
+        let message = OscMessage::new("/theremin/quit", Vec::new()).encode();
+        let mut packet = bundle(&[message]);
+
+        // tamanho maior que o resto do pacote
+        packet[16..20].copy_from_slice(&1000u32.to_be_bytes());
+        assert!(decode_packet(&packet).is_err());
+
+        // -1 como i32: não pode estourar a soma nem entrar em pânico
+        packet[16..20].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(decode_packet(&packet).is_err());
+    }
+
+    #[test]
+    fn non_finite_numbers_are_rejected() {
+        let message = OscMessage::new("/theremin/tempo", vec![OscArg::Float(f32::NAN)]);
+        assert_eq!(command_for_message(&message), None);
+        assert_eq!(text_command("/theremin/tempo nan"), None);
+        assert_eq!(text_command("/theremin/transpose inf"), None);
+    }
+
+    #[test]
+    fn transpose_arguments_are_clamped() {
+        assert_eq!(
+            text_command("/theremin/transpose 2147483647"),
+            Some(Command::Transpose(PITCH_TRANSPOSE_LIMIT))
+        );
+        assert_eq!(
+            text_command("/theremin/root -100"),
+            Some(Command::SetTranspose(-PITCH_TRANSPOSE_LIMIT))
+        );
+    }
+
+    #[test]
+    fn messages_map_to_commands() {
+        let message = OscMessage::new("/theremin/sound", vec![OscArg::Float(0.0)]);
+        assert_eq!(
+            command_for_message(&message),
+            Some(Command::SetSound(false))
+        );
+
+        let message = OscMessage::new("/theremin/nada", Vec::new());
+        assert_eq!(command_for_message(&message), None);
+
+        assert_eq!(
+            text_command("/theremin/scale Maior"),
+            Some(Command::SetScale(Scale::Major))
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::config::{
+    PITCH_LOWEST_NOTE, PITCH_OCTAVES, PITCH_REFERENCE_A4, PITCH_SCALE, PITCH_TRANSPOSE,
+    PITCH_TRANSPOSE_LIMIT,
+};
+
+/// nota MIDI do A4
+const A4_MIDI_NOTE: i32 = 69;
+
+const NOTE_NAMES: &[&str] = &[
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// escala usada para montar a tabela de notas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    /// C D E G A, a mesma escala da tabela original
+    PentatonicMajor,
+    PentatonicMinor,
+    Major,
+    Minor,
+}
+
+impl Scale {
+    pub const ALL: &[Scale] = &[
+        Scale::PentatonicMajor,
+        Scale::PentatonicMinor,
+        Scale::Major,
+        Scale::Minor,
+    ];
+
+    /// semitons a partir da fundamental
+    pub fn intervals(&self) -> &'static [i32] {
+        match self {
+            Scale::PentatonicMajor => &[0, 2, 4, 7, 9],
+            Scale::PentatonicMinor => &[0, 3, 5, 7, 10],
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::Minor => &[0, 2, 3, 5, 7, 8, 10],
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scale::PentatonicMajor => "Pentatonica maior",
+            Scale::PentatonicMinor => "Pentatonica menor",
+            Scale::Major => "Maior",
+            Scale::Minor => "Menor",
+        }
+    }
+
+    /// escala de sete notas que contém esta, com a mesma tônica
+    pub fn heptatonic(&self) -> Self {
+        match self {
+            Scale::PentatonicMajor => Scale::Major,
+            Scale::PentatonicMinor => Scale::Minor,
+            scale => *scale,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Scale::ALL
+            .iter()
+            .position(|scale| scale == self)
+            .unwrap_or(0);
+        Scale::ALL[(index + 1) % Scale::ALL.len()]
+    }
+}
+
+/// tabela de notas do eixo Y, recalculada a partir da nota base, escala e número de oitavas
+#[derive(Debug, Clone)]
+pub struct PitchMapping {
+    lowest_note: i32,
+    octaves: u32,
+    reference_a4: f32,
+    transpose: i32,
+    scale: Scale,
+    /// altura contínua ao longo do eixo Y, sem quantizar na escala
+    continuous: bool,
+    notes: Vec<i32>,
+}
+
+impl PitchMapping {
+    pub fn new(
+        lowest_note: i32,
+        octaves: u32,
+        reference_a4: f32,
+        transpose: i32,
+        scale: Scale,
+    ) -> Self {
+        let mut mapping = PitchMapping {
+            lowest_note,
+            octaves: octaves.max(1),
+            reference_a4,
+            transpose,
+            scale,
+            continuous: false,
+            notes: Vec::new(),
+        };
+        mapping.rebuild_table();
+        mapping
+    }
+
+    fn rebuild_table(&mut self) {
+        let degrees = self.scale.intervals().len() * self.octaves as usize;
+        self.notes = (0..degrees)
+            .map(|degree| self.degree_to_note(degree))
+            .collect();
+    }
+
+    pub fn set_transpose(&mut self, transpose: i32) {
+        self.transpose = transpose.clamp(-PITCH_TRANSPOSE_LIMIT, PITCH_TRANSPOSE_LIMIT);
+    }
+
+    pub fn transpose(&self) -> i32 {
+        self.transpose
+    }
+
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.scale = scale;
+        self.rebuild_table();
+    }
+
+    pub fn scale(&self) -> Scale {
+        self.scale
+    }
+
+    pub fn set_continuous(&mut self, continuous: bool) {
+        self.continuous = continuous;
+    }
+
+    pub fn is_continuous(&self) -> bool {
+        self.continuous
+    }
+
+    pub fn octaves(&self) -> u32 {
+        self.octaves
+    }
+
+    pub fn reference_a4(&self) -> f32 {
+        self.reference_a4
+    }
+
+    /// nota MIDI (sem transposição) de um grau da escala, contando a partir da nota base;
+    /// graus além da tabela continuam nas oitavas seguintes
+    pub fn degree_to_note(&self, degree: usize) -> i32 {
+        self.degree_to_note_in(self.scale, degree)
+    }
+
+    /// como degree_to_note, mas contando os graus em outra escala
+    pub fn degree_to_note_in(&self, scale: Scale, degree: usize) -> i32 {
+        let intervals = scale.intervals();
+        let octave = (degree / intervals.len()) as i32;
+        self.lowest_note + 12 * octave + intervals[degree % intervals.len()]
+    }
+
+    /// índice da faixa em que Y (em [-1, 1]) se encontra
+    pub fn index_for_position(&self, y: f32) -> usize {
+        let t = ((y + 1.0) / 2.0).clamp(0.0, 1.0);
+        let index = (t * self.notes.len() as f32) as usize;
+        index.min(self.notes.len() - 1)
+    }
+
+    /// nota MIDI soando (já transposta) para a posição Y
+    pub fn note_for_position(&self, y: f32) -> i32 {
+        self.notes[self.index_for_position(y)] + self.transpose
+    }
+
+    pub fn frequency_for_position(&self, y: f32) -> f32 {
+        if self.continuous {
+            let t = ((y + 1.0) / 2.0).clamp(0.0, 1.0);
+            let note = self.lowest_note as f32 + t * 12.0 * self.octaves as f32;
+            return midi_to_frequency(note + self.transpose as f32, self.reference_a4);
+        }
+        self.note_to_frequency(self.note_for_position(y))
+    }
+
+    pub fn note_to_frequency(&self, note: i32) -> f32 {
+        midi_to_frequency(note as f32, self.reference_a4)
+    }
+}
+
+pub fn midi_to_frequency(note: f32, reference_a4: f32) -> f32 {
+    reference_a4 * 2f32.powf((note - A4_MIDI_NOTE as f32) / 12.0)
+}
+
+/// nota MIDI contínua (fracionária) para uma frequência
+pub fn frequency_to_midi(frequency: f32, reference_a4: f32) -> f32 {
+    A4_MIDI_NOTE as f32 + 12.0 * (frequency.max(1.0) / reference_a4).log2()
+}
+
+/// nome da nota com a oitava, p.ex. "A4"
+pub fn note_name(note: i32) -> String {
+    let name = NOTE_NAMES[note.rem_euclid(12) as usize];
+    format!("{}{}", name, note.div_euclid(12) - 1)
+}
+
+/// nota mais próxima da frequência e o desvio em cents em relação a ela
+pub fn nearest_note(frequency: f32, reference_a4: f32) -> (i32, f32) {
+    let midi = frequency_to_midi(frequency, reference_a4);
+    let note = midi.round();
+    (note as i32, (midi - note) * 100.0)
+}
+
+impl Default for PitchMapping {
+    fn default() -> Self {
+        PitchMapping::new(
+            PITCH_LOWEST_NOTE,
+            PITCH_OCTAVES,
+            PITCH_REFERENCE_A4,
+            PITCH_TRANSPOSE,
+            PITCH_SCALE,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_mapping_keeps_the_original_note_table() {
+        // C3 D3 E3 G3 A3 C4 D4 E4 G4 A4, de baixo para cima
+        let expected = [48, 50, 52, 55, 57, 60, 62, 64, 67, 69];
+        let mapping = PitchMapping::default();
+        let band = 2.0 / expected.len() as f32;
+
+        for (index, &note) in expected.iter().enumerate() {
+            let y = -1.0 + (index as f32 + 0.5) * band;
+            assert_eq!(mapping.index_for_position(y), index);
+            assert_eq!(mapping.note_for_position(y), note);
+        }
+
+        assert_eq!(mapping.note_for_position(-1.0), 48);
+        assert_eq!(mapping.note_for_position(1.0), 69);
+        assert!((mapping.frequency_for_position(1.0) - 440.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    config::{STRING_BRIGHTNESS, STRING_DAMPING, STRING_SYNC_SAMPLES},
+    rng::Rng,
+};
+
+/// corda dedilhada por Karplus-Strong: ruído numa linha de atraso realimentada e filtrada
+pub struct PluckedString {
+    /// (número, força) da última palhetada; clones compartilham, e cada um toca cada
+    /// palhetada uma vez (a corda do harmonizador é palhetada junto com a principal)
+    plucks: Arc<Mutex<(u64, f32)>>,
+    played: u64,
+    sync_counter: usize,
+    delay: Vec<f32>,
+    index: usize,
+    rng: Rng,
+}
+
+impl PluckedString {
+    pub fn new() -> Self {
+        Self::with_plucks(Arc::new(Mutex::new((0, 0.0))))
+    }
+
+    fn with_plucks(plucks: Arc<Mutex<(u64, f32)>>) -> Self {
+        // palhetadas anteriores à criação não soam
+        let played = plucks.lock().unwrap().0;
+        PluckedString {
+            plucks,
+            played,
+            sync_counter: 0,
+            delay: Vec::new(),
+            index: 0,
+            rng: Rng::new(),
+        }
+    }
+
+    /// `strength` em [0, 1]
+    pub fn pluck(&self, strength: f32) {
+        let mut plucks = self.plucks.lock().unwrap();
+        *plucks = (plucks.0 + 1, strength.clamp(0.0, 1.0));
+    }
+
+    pub fn next_sample(&mut self, frequency: f32, sample_rate: u32) -> f32 {
+        if self.sync_counter == 0 {
+            let (count, strength) = *self.plucks.lock().unwrap();
+            if count != self.played {
+                self.played = count;
+                self.excite(frequency, strength, sample_rate);
+            }
+        }
+        self.sync_counter = (self.sync_counter + 1) % STRING_SYNC_SAMPLES;
+
+        if self.delay.len() < 2 {
+            return 0.0;
+        }
+
+        // média ponderada no laço: mais peso na amostra atual deixa a corda mais brilhante
+        let next_index = (self.index + 1) % self.delay.len();
+        let current = self.delay[self.index];
+        let next = self.delay[next_index];
+        let blend = 0.5 * (1.0 - STRING_BRIGHTNESS);
+
+        self.delay[self.index] = STRING_DAMPING * ((1.0 - blend) * current + blend * next);
+        self.index = next_index;
+
+        current
+    }
+
+    /// preenche a linha de atraso (um período da nota) com ruído filtrado pelo brilho
+    fn excite(&mut self, frequency: f32, strength: f32, sample_rate: u32) {
+        let length = (sample_rate as f32 / frequency.max(20.0)).round().max(2.0) as usize;
+
+        let mut previous = 0.0;
+        self.delay = (0..length)
+            .map(|_| {
+                let noise = self.rng.next_bipolar();
+                previous += (noise - previous) * STRING_BRIGHTNESS.max(0.05);
+                previous * strength
+            })
+            .collect();
+        self.index = 0;
+    }
+}
+
+impl Clone for PluckedString {
+    fn clone(&self) -> Self {
+        // a corda vibrando pertence à instância que está tocando
+        Self::with_plucks(self.plucks.clone())
+    }
+}
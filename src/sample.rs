@@ -0,0 +1,48 @@
+use rodio::{Decoder, Source};
+use std::error::Error;
+use std::fs::File;
+
+/// áudio decodificado em memória, convertido para mono
+pub struct SampleBuffer {
+    pub data: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl SampleBuffer {
+    /// decodifica um arquivo de áudio (WAV, etc.) e mistura os canais em mono
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        let decoder = Decoder::try_from(file)?;
+
+        let channels = decoder.channels().max(1) as usize;
+        let sample_rate = decoder.sample_rate();
+        let interleaved: Vec<f32> = decoder.collect();
+
+        let data: Vec<f32> = interleaved
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect();
+
+        if data.is_empty() {
+            return Err(format!("Arquivo de áudio vazio: {}", path).into());
+        }
+
+        Ok(SampleBuffer { data, sample_rate })
+    }
+
+    /// leitura com interpolação linear; fora do buffer retorna silêncio
+    pub fn read(&self, position: f64) -> f32 {
+        if position < 0.0 {
+            return 0.0;
+        }
+
+        let index = position as usize;
+        let fraction = (position - index as f64) as f32;
+
+        match (self.data.get(index), self.data.get(index + 1)) {
+            (Some(&a), Some(&b)) => a + (b - a) * fraction,
+            (Some(&a), None) => a,
+            _ => 0.0,
+        }
+    }
+}
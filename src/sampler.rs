@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    config::{PITCH_REFERENCE_A4, SAMPLER_CROSSFADE_MS, SAMPLER_LOOP_END, SAMPLER_LOOP_START},
+    pitch::{frequency_to_midi, midi_to_frequency},
+    sample::SampleBuffer,
+};
+
+/// uma amostra cobrindo uma faixa de notas, com pontos de loop
+#[derive(Clone)]
+struct SamplerZone {
+    buffer: Arc<SampleBuffer>,
+    root_note: i32,
+    low_note: i32,
+    high_note: i32,
+    loop_start: f64,
+    loop_end: f64,
+    crossfade: f64,
+}
+
+impl SamplerZone {
+    fn new(buffer: SampleBuffer, root_note: i32, low_note: i32, high_note: i32) -> Self {
+        let length = buffer.data.len() as f64;
+        let loop_start = (length * SAMPLER_LOOP_START as f64).floor();
+        let loop_end = (length * SAMPLER_LOOP_END as f64)
+            .floor()
+            .max(loop_start + 1.0);
+        let crossfade = (SAMPLER_CROSSFADE_MS as f64 * buffer.sample_rate as f64 / 1000.0)
+            .min(loop_start)
+            .min((loop_end - loop_start) / 2.0);
+
+        SamplerZone {
+            buffer: Arc::new(buffer),
+            root_note,
+            low_note,
+            high_note,
+            loop_start,
+            loop_end,
+            crossfade,
+        }
+    }
+
+    /// lê a amostra aplicando o crossfade no fim do loop
+    fn read(&self, position: f64) -> f32 {
+        let fade_start = self.loop_end - self.crossfade;
+        if self.crossfade <= 0.0 || position < fade_start {
+            return self.buffer.read(position);
+        }
+
+        let loop_length = self.loop_end - self.loop_start;
+        let mix = ((position - fade_start) / self.crossfade) as f32;
+        self.buffer.read(position) * (1.0 - mix) + self.buffer.read(position - loop_length) * mix
+    }
+}
+
+/// voz de sampler: reproduz amostras em loop reafinadas para a frequência atual
+#[derive(Clone)]
+pub struct SamplerVoice {
+    zones: Vec<SamplerZone>,
+    current_zone: usize,
+    position: f64,
+}
+
+impl SamplerVoice {
+    /// carrega as zonas (arquivo, nota raiz, nota mais grave, nota mais aguda)
+    pub fn load(zones: &[(&str, i32, i32, i32)]) -> Result<Self, Box<dyn Error>> {
+        let mut loaded = Vec::new();
+
+        for &(path, root_note, low_note, high_note) in zones {
+            match SampleBuffer::load(path) {
+                Ok(buffer) => loaded.push(SamplerZone::new(buffer, root_note, low_note, high_note)),
+                Err(e) => eprintln!("[ERROR] Erro ao carregar amostra {}: {}", path, e),
+            }
+        }
+
+        if loaded.is_empty() {
+            return Err("Nenhuma amostra do sampler foi carregada".into());
+        }
+
+        Ok(SamplerVoice {
+            zones: loaded,
+            current_zone: 0,
+            position: 0.0,
+        })
+    }
+
+    pub fn zone_count(&self) -> usize {
+        self.zones.len()
+    }
+
+    pub fn restart(&mut self) {
+        self.position = 0.0;
+    }
+
+    fn zone_for_frequency(&self, frequency: f32) -> usize {
+        let note = frequency_to_midi(frequency, PITCH_REFERENCE_A4).round() as i32;
+
+        if let Some(index) = self
+            .zones
+            .iter()
+            .position(|zone| note >= zone.low_note && note <= zone.high_note)
+        {
+            return index;
+        }
+
+        // fora de todas as faixas: usa a zona com a nota raiz mais próxima
+        self.zones
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, zone)| (zone.root_note - note).abs())
+            .map_or(0, |(index, _)| index)
+    }
+
+    pub fn next_sample(&mut self, frequency: f32, output_rate: u32) -> f32 {
+        let zone_index = self.zone_for_frequency(frequency);
+        if zone_index != self.current_zone {
+            self.current_zone = zone_index;
+            self.restart();
+        }
+
+        let zone = &self.zones[zone_index];
+        let sample = zone.read(self.position);
+
+        let root_frequency = midi_to_frequency(zone.root_note as f32, PITCH_REFERENCE_A4);
+        let ratio = (frequency / root_frequency) as f64;
+        self.position += ratio * zone.buffer.sample_rate as f64 / output_rate as f64;
+
+        let loop_length = zone.loop_end - zone.loop_start;
+        while self.position >= zone.loop_end {
+            self.position -= loop_length;
+        }
+
+        sample
+    }
+}
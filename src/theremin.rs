@@ -1,18 +1,212 @@
 use rodio::{OutputStream, OutputStreamBuilder, Sink, Source};
 use std::f32::consts::PI;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    amplitude::{AmplitudeCurve, AmplitudeMapping},
+    arpeggiator::{ArpPattern, ArpXControl, Arpeggiator},
+    chord::{
+        ChordType, ChordVoice, ChordVoicing, MAX_CHORD_NOTES, build_chord, degree_for_position,
+    },
+    clock::{ClockStatus, TempoClock},
+    config::{
+        ARP_AMPLITUDE, ARP_DEFAULT_RATE, ARP_INTERVALS, ARP_MAX_RATE, ARP_MIN_RATE,
+        CHORD_AMPLITUDE, CLOCK_BPM_STEP, DRUM_GAIN, DRUM_ZONES, FORMANT_ROTATION_RANGE,
+        GRANULAR_FILE, GRANULAR_GAIN, HARMONY_LEVEL, LOOPER_LEVEL, MARKER_LOSS_POLICY,
+        MIDI_EXPORT_PREFIX, MIDI_OUTPUT_BACKEND, NOISE_GAIN, SAMPLER_ZONES, STRING_MAX_SPEED,
+        STRING_MIN_PLUCK,
+    },
+    drum_pads::{DrumPads, OneShotPlayer, load_drum_kit},
+    formant::{FormantAxis, FormantFilter},
+    granular::{GranularEngine, GranularYControl},
+    harmonizer::HarmonyInterval,
+    looper::{Looper, LooperCommand, LooperStatus},
+    marker_loss::MarkerLossPolicy,
+    master_bus::{MasterBus, MeterLevels},
+    midi_file::MidiRecorder,
+    midi_out::{LiveMidi, MidiOutput, open_output},
+    mpe::MpeOutput,
+    noise::{NoiseColor, NoiseVoice, NoiseXControl},
+    pitch::{PitchMapping, Scale},
+    pluck::PluckedString,
+    sampler::SamplerVoice,
+    ui::NormalizedPosition,
+};
+
+const CHANNELS: u16 = 2; // Estéreo
+
+/// suavização do ganho de presença do marcador, evita degraus a cada quadro
+const PRESENCE_SMOOTHING: f32 = 0.001;
+
+/// motor de som usado pela fonte
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VoiceType {
+    Sine,
+    Sampler,
+    Granular,
+    /// oscilador senoidal misturado a ruído da cor escolhida
+    Noise(NoiseColor),
+    /// corda dedilhada (Karplus-Strong), palhetada a cada troca de nota
+    String,
+}
+
+impl VoiceType {
+    pub const ALL: &[VoiceType] = &[
+        VoiceType::Sine,
+        VoiceType::Sampler,
+        VoiceType::Granular,
+        VoiceType::Noise(NoiseColor::White),
+        VoiceType::Noise(NoiseColor::Pink),
+        VoiceType::Noise(NoiseColor::Brown),
+        VoiceType::String,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VoiceType::Sine => "Seno",
+            VoiceType::Sampler => "Sampler",
+            VoiceType::Granular => "Granular",
+            VoiceType::Noise(NoiseColor::White) => "Ruido branco",
+            VoiceType::Noise(NoiseColor::Pink) => "Ruido rosa",
+            VoiceType::Noise(NoiseColor::Brown) => "Ruido marrom",
+            VoiceType::String => "Corda",
+        }
+    }
+}
+
+/// como a posição do marcador é transformada em som
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayMode {
+    Theremin,
+    DrumPads,
+    Arpeggiator,
+    Chord,
+}
+
+impl PlayMode {
+    pub const ALL: &[PlayMode] = &[
+        PlayMode::Theremin,
+        PlayMode::DrumPads,
+        PlayMode::Arpeggiator,
+        PlayMode::Chord,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlayMode::Theremin => "Theremin",
+            PlayMode::DrumPads => "Pads",
+            PlayMode::Arpeggiator => "Arpejo",
+            PlayMode::Chord => "Acorde",
+        }
+    }
+}
 
 struct ThereminState {
     amplitude: f32,
     frequency: f32,
     enabled: bool,
+    voice: VoiceType,
+    position: (f32, f32),
+    chord: [f32; MAX_CHORD_NOTES],
+    chord_len: usize,
+    /// ganho aplicado à voz conforme a política de perda do marcador
+    presence: f32,
+    /// razão de frequência da voz paralela; `None` com o harmonizador desligado
+    harmony_ratio: Option<f32>,
+    /// vogal do filtro de formantes em [0, 1]; `None` com o filtro desligado
+    formant: Option<f32>,
+}
+
+/// um conjunto de vozes com estado próprio; clones compartilham só os controles
+/// (eixos do ruído e do granular, palhetadas da corda)
+#[derive(Clone)]
+struct VoiceBank {
+    phase: f32,
+    sampler: Option<SamplerVoice>,
+    granular: Option<GranularEngine>,
+    noise: NoiseVoice,
+    string: PluckedString,
+}
+
+impl VoiceBank {
+    fn new() -> Self {
+        VoiceBank {
+            phase: 0.0,
+            sampler: None,
+            granular: None,
+            noise: NoiseVoice::new(),
+            string: PluckedString::new(),
+        }
+    }
+
+    /// amostra da voz e o ganho a aplicar sobre ela
+    fn next_sample(
+        &mut self,
+        voice: VoiceType,
+        frequency: f32,
+        amplitude: f32,
+        (x, y): (f32, f32),
+        sample_rate: u32,
+    ) -> (f32, f32) {
+        match voice {
+            VoiceType::Sine => (self.next_sine(frequency, sample_rate), amplitude),
+            VoiceType::Sampler => match &mut self.sampler {
+                Some(sampler) => (sampler.next_sample(frequency, sample_rate), amplitude),
+                None => (0.0, 0.0),
+            },
+            // no granular o X escolhe a posição de leitura, então o volume é fixo
+            VoiceType::Granular => match &mut self.granular {
+                Some(granular) => (
+                    granular.next_sample(x, y, frequency, sample_rate),
+                    GRANULAR_GAIN,
+                ),
+                None => (0.0, 0.0),
+            },
+            // o X pode estar ocupado com a mistura ou a cor do ruído, então o volume é fixo
+            VoiceType::Noise(color) => {
+                let oscillator = self.next_sine(frequency, sample_rate);
+                (self.noise.next_sample(oscillator, color, x), NOISE_GAIN)
+            }
+            VoiceType::String => (self.string.next_sample(frequency, sample_rate), amplitude),
+        }
+    }
+
+    fn next_sine(&mut self, frequency: f32, sample_rate: u32) -> f32 {
+        self.phase += 2.0 * PI * frequency / sample_rate as f32;
+        if self.phase > 2.0 * PI {
+            self.phase -= 2.0 * PI;
+        }
+
+        self.phase.sin()
+    }
+
+    fn restart(&mut self) {
+        if let Some(sampler) = &mut self.sampler {
+            sampler.restart();
+        }
+    }
 }
 
 pub struct ThereminSource {
     state: Arc<Mutex<ThereminState>>,
     sample_rate: u32,
-    phase: f32,
+    master: MasterBus,
+    active_voice: VoiceType,
+    voices: VoiceBank,
+    /// cópia da voz selecionada tocada pelo harmonizador
+    harmony_voices: VoiceBank,
+    drums: Option<OneShotPlayer>,
+    arpeggiator: Arpeggiator,
+    chord_voice: ChordVoice,
+    formant_filter: FormantFilter,
+    looper: Looper,
+    clock: TempoClock,
+    quantized_frequency: f32,
+    presence_gain: f32,
+    /// amostra do quadro atual, repetida em todos os canais
+    frame_sample: f32,
+    pending_channels: u16,
 }
 
 impl ThereminSource {
@@ -21,20 +215,154 @@ impl ThereminSource {
             amplitude,
             frequency,
             enabled: true,
+            voice: VoiceType::Sine,
+            position: (0.0, 0.0),
+            chord: [0.0; MAX_CHORD_NOTES],
+            chord_len: 0,
+            presence: 1.0,
+            harmony_ratio: None,
+            formant: None,
         }));
+        let voices = VoiceBank::new();
         Self {
             state,
             sample_rate,
-            phase: 0.0,
+            master: MasterBus::new(sample_rate),
+            active_voice: VoiceType::Sine,
+            harmony_voices: voices.clone(),
+            voices,
+            drums: None,
+            arpeggiator: Arpeggiator::new(),
+            chord_voice: ChordVoice::new(),
+            formant_filter: FormantFilter::new(),
+            looper: Looper::new(sample_rate),
+            clock: TempoClock::new(sample_rate),
+            quantized_frequency: frequency,
+            presence_gain: 1.0,
+            frame_sample: 0.0,
+            pending_channels: 0,
         }
     }
 
+    pub fn clock(&self) -> &TempoClock {
+        &self.clock
+    }
+
+    pub fn send_looper_command(&self, command: LooperCommand) {
+        self.looper.send(command);
+    }
+
+    pub fn looper_status(&self) -> LooperStatus {
+        self.looper.status()
+    }
+
+    /// frequências do acorde tocado pelo banco de osciladores; vazio desliga o acorde
+    pub fn update_chord(&self, frequencies: &[f32]) {
+        let mut state = self.state.lock().unwrap();
+        let len = frequencies.len().min(MAX_CHORD_NOTES);
+        state.chord[..len].copy_from_slice(&frequencies[..len]);
+        state.chord_len = len;
+    }
+
+    pub fn arpeggiator(&self) -> &Arpeggiator {
+        &self.arpeggiator
+    }
+
+    pub fn with_drums(mut self, drums: OneShotPlayer) -> Self {
+        self.drums = Some(drums);
+        self
+    }
+
+    pub fn trigger_drum(&self, sample: usize) {
+        if let Some(drums) = &self.drums {
+            drums.trigger(sample);
+        }
+    }
+
+    pub fn with_sampler(mut self, sampler: SamplerVoice) -> Self {
+        self.harmony_voices.sampler = Some(sampler.clone());
+        self.voices.sampler = Some(sampler);
+        self
+    }
+
+    pub fn with_granular(mut self, granular: GranularEngine) -> Self {
+        self.harmony_voices.granular = Some(granular.clone());
+        self.voices.granular = Some(granular);
+        self
+    }
+
+    /// posição normalizada do marcador, usada por vozes que leem X/Y diretamente
+    pub fn update_position(&self, x: f32, y: f32) {
+        let mut state = self.state.lock().unwrap();
+        state.position = (x, y);
+    }
+
+    pub fn set_voice(&self, voice: VoiceType) {
+        let mut state = self.state.lock().unwrap();
+        state.voice = voice;
+    }
+
+    pub fn get_voice(&self) -> VoiceType {
+        let state = self.state.lock().unwrap();
+        state.voice
+    }
+
+    /// vozes que podem ser selecionadas (as que dependem de arquivos só se carregadas)
+    pub fn is_voice_available(&self, voice: VoiceType) -> bool {
+        match voice {
+            VoiceType::Sine => true,
+            VoiceType::Sampler => self.voices.sampler.is_some(),
+            VoiceType::Granular => self.voices.granular.is_some(),
+            VoiceType::Noise(_) | VoiceType::String => true,
+        }
+    }
+
+    pub fn pluck_string(&self, strength: f32) {
+        self.voices.string.pluck(strength);
+    }
+
+    pub fn set_noise_x_control(&self, control: NoiseXControl) {
+        self.voices.noise.set_x_control(control);
+    }
+
+    pub fn get_noise_x_control(&self) -> NoiseXControl {
+        self.voices.noise.get_x_control()
+    }
+
+    pub fn set_granular_y_control(&self, control: GranularYControl) {
+        if let Some(granular) = &self.voices.granular {
+            granular.set_y_control(control);
+        }
+    }
+
+    pub fn get_granular_y_control(&self) -> Option<GranularYControl> {
+        self.voices
+            .granular
+            .as_ref()
+            .map(|granular| granular.get_y_control())
+    }
+
     pub fn update_parameters(&self, amplitude: f32, frequency: f32) {
         let mut state = self.state.lock().unwrap();
         state.amplitude = amplitude;
         state.frequency = frequency;
     }
 
+    pub fn update_harmony(&self, ratio: Option<f32>) {
+        let mut state = self.state.lock().unwrap();
+        state.harmony_ratio = ratio;
+    }
+
+    pub fn update_formant(&self, vowel: Option<f32>) {
+        let mut state = self.state.lock().unwrap();
+        state.formant = vowel;
+    }
+
+    pub fn set_presence(&self, presence: f32) {
+        let mut state = self.state.lock().unwrap();
+        state.presence = presence;
+    }
+
     pub fn set_enabled(&self, enabled: bool) {
         let mut state = self.state.lock().unwrap();
         state.enabled = enabled;
@@ -55,37 +383,141 @@ impl ThereminSource {
         state.frequency
     }
 
+    pub fn set_compressor_enabled(&self, enabled: bool) {
+        self.master.set_compressor_enabled(enabled);
+    }
+
+    pub fn is_compressor_enabled(&self) -> bool {
+        self.master.is_compressor_enabled()
+    }
+
+    pub fn meter_levels(&self) -> MeterLevels {
+        self.master.levels()
+    }
+
     fn generate_sample(&mut self) -> f32 {
-        let (amplitude, frequency, enabled) = {
+        let (
+            amplitude,
+            frequency,
+            enabled,
+            voice,
+            (x, y),
+            chord,
+            chord_len,
+            presence,
+            harmony,
+            formant,
+        ) = {
             let state = self.state.lock().unwrap();
-            (state.amplitude, state.frequency, state.enabled)
+            (
+                state.amplitude,
+                state.frequency,
+                state.enabled,
+                state.voice,
+                state.position,
+                state.chord,
+                state.chord_len,
+                state.presence,
+                state.harmony_ratio,
+                state.formant,
+            )
         };
 
+        // o relógio continua correndo com o som desligado
+        let tick = self.clock.tick();
+
         if !enabled {
-            return 0.0;
+            // continua passando pelo master para esvaziar o look-ahead e o medidor
+            return self.master.process(0.0);
         }
 
-        self.phase += 2.0 * PI * frequency / self.sample_rate as f32;
-        if self.phase > 2.0 * PI {
-            self.phase -= 2.0 * PI;
+        // com quantização, a nota só muda no início da próxima subdivisão
+        if !self.clock.is_quantize_enabled() || tick.subdivision {
+            self.quantized_frequency = frequency;
+        }
+        let frequency = self.quantized_frequency;
+
+        if voice != self.active_voice {
+            self.active_voice = voice;
+            self.voices.restart();
+            self.harmony_voices.restart();
         }
 
-        let sample = self.phase.sin() * amplitude;
-        sample.clamp(-0.8, 0.8)
+        // no modo arpejo a nota e a articulação vêm do relógio do arpejador
+        let (frequency, articulation) = self
+            .arpeggiator
+            .next(self.sample_rate)
+            .unwrap_or((frequency, 1.0));
+
+        // no modo acorde o banco de osciladores substitui a voz selecionada
+        let (sample, gain) = if chord_len > 0 {
+            let sample = self
+                .chord_voice
+                .next_sample(&chord[..chord_len], self.sample_rate);
+            (sample, amplitude)
+        } else {
+            self.voices
+                .next_sample(voice, frequency, amplitude, (x, y), self.sample_rate)
+        };
+
+        // a voz paralela é a mesma voz uma nota acima: segue a articulação e o ganho
+        // da principal, e a corda é palhetada junto e decai sozinha
+        let harmony = match harmony {
+            Some(ratio) if chord_len == 0 => {
+                let (harmony, _) = self.harmony_voices.next_sample(
+                    voice,
+                    frequency * ratio,
+                    amplitude,
+                    (x, y),
+                    self.sample_rate,
+                );
+                harmony * HARMONY_LEVEL
+            }
+            _ => 0.0,
+        };
+
+        // estágio de efeito depois do oscilador: a voz "canta" a vogal escolhida
+        let voice_sample = match formant {
+            Some(vowel) => self
+                .formant_filter
+                .process(sample + harmony, vowel, self.sample_rate),
+            None => sample + harmony,
+        };
+
+        let drums = match &mut self.drums {
+            Some(drums) => drums.next_sample(self.sample_rate) * DRUM_GAIN,
+            None => 0.0,
+        };
+
+        self.presence_gain += (presence - self.presence_gain) * PRESENCE_SMOOTHING;
+
+        // os pads já disparados não são cortados pela perda do marcador
+        let live = voice_sample * gain * articulation * self.presence_gain + drums;
+        let looped = self.looper.process(live);
+
+        self.master
+            .process(live + looped * LOOPER_LEVEL + tick.click)
     }
+
 }
 
 impl Iterator for ThereminSource {
     type Item = f32;
 
+    /// as vozes avançam uma vez por quadro (sample_rate vezes por segundo), não por canal
     fn next(&mut self) -> Option<Self::Item> {
-        Some(self.generate_sample())
+        if self.pending_channels == 0 {
+            self.frame_sample = self.generate_sample();
+            self.pending_channels = CHANNELS;
+        }
+        self.pending_channels -= 1;
+        Some(self.frame_sample)
     }
 }
 
 impl Source for ThereminSource {
     fn channels(&self) -> u16 {
-        2 // Estéreo
+        CHANNELS
     }
 
     fn sample_rate(&self) -> u32 {
@@ -107,6 +539,29 @@ pub struct ThereminController {
     source: ThereminSource,
     last_amplitude: f32,
     last_frequency: f32,
+    last_position: (f32, f32),
+    amplitude_mapping: AmplitudeMapping,
+    pitch_mapping: PitchMapping,
+    play_mode: PlayMode,
+    drum_pads: DrumPads,
+    arp_pattern: ArpPattern,
+    arp_x_control: ArpXControl,
+    chord_type: ChordType,
+    chord_degree: usize,
+    chord_voicing: ChordVoicing,
+    loss_policy: MarkerLossPolicy,
+    lost_since: Option<Instant>,
+    /// ganho da política de perda, aplicado também ao MIDI
+    presence: f32,
+    harmony_interval: HarmonyInterval,
+    formant_axis: FormantAxis,
+    rotation: f32,
+    last_detection: Option<Instant>,
+    marker_speed: f32,
+    plucked_note: Option<i32>,
+    midi_recorder: MidiRecorder,
+    live_midi: Option<LiveMidi>,
+    mpe: Option<MpeOutput>,
 }
 
 impl ThereminController {
@@ -114,7 +569,32 @@ impl ThereminController {
         let stream_handle = OutputStreamBuilder::open_default_stream()?;
         let sink = Sink::connect_new(&stream_handle.mixer());
 
-        let source = ThereminSource::new(0.5, 440.0, 44100);
+        let mut source = ThereminSource::new(0.5, 440.0, 44100);
+
+        match SamplerVoice::load(SAMPLER_ZONES) {
+            Ok(sampler) => {
+                log!(
+                    "[START] Sampler carregado ({} zona(s))",
+                    sampler.zone_count()
+                );
+                source = source.with_sampler(sampler);
+            }
+            Err(e) => log!("[INFO] Sampler indisponível: {}", e),
+        }
+
+        match GranularEngine::load(GRANULAR_FILE) {
+            Ok(granular) => {
+                log!("[START] Motor granular carregado: {}", GRANULAR_FILE);
+                source = source.with_granular(granular);
+            }
+            Err(e) => log!("[INFO] Motor granular indisponível: {}", e),
+        }
+
+        let (drum_pads, drums) = load_drum_kit(DRUM_ZONES);
+        source = source.with_drums(drums);
+
+        let midi_recorder = MidiRecorder::new(source.clock().status().bpm);
+
         sink.append(source.clone());
         sink.set_volume(0.7);
 
@@ -124,54 +604,406 @@ impl ThereminController {
             source,
             last_amplitude: 0.5,
             last_frequency: 440.0,
+            last_position: (0.0, 0.0),
+            amplitude_mapping: AmplitudeMapping::default(),
+            pitch_mapping: PitchMapping::default(),
+            play_mode: PlayMode::Theremin,
+            drum_pads,
+            arp_pattern: ArpPattern::Up,
+            arp_x_control: ArpXControl::Rate,
+            chord_type: ChordType::Triad,
+            chord_degree: 0,
+            chord_voicing: ChordVoicing::Inversion(0),
+            loss_policy: MARKER_LOSS_POLICY,
+            lost_since: None,
+            presence: 1.0,
+            harmony_interval: HarmonyInterval::Off,
+            formant_axis: FormantAxis::Off,
+            rotation: 0.0,
+            last_detection: None,
+            marker_speed: 0.0,
+            plucked_note: None,
+            midi_recorder,
+            live_midi: None,
+            mpe: None,
         })
     }
 
     pub fn update_from_position(&mut self, x: f32, y: f32) {
-        let (frequency, amplitude) = Self::map_position_to_audio(x, y);
+        if self.lost_since.take().is_some() {
+            self.presence = 1.0;
+            self.source.set_presence(1.0);
+        }
+        self.track_speed(x, y);
+        self.apply_position(x, y);
+    }
+
+    /// velocidade do marcador entre quadros, em unidades normalizadas por segundo
+    fn track_speed(&mut self, x: f32, y: f32) {
+        let now = Instant::now();
+        if let Some(previous) = self.last_detection.replace(now) {
+            let elapsed = now.duration_since(previous).as_secs_f32().max(0.001);
+            let (last_x, last_y) = self.last_position;
+            self.marker_speed = (x - last_x).hypot(y - last_y) / elapsed;
+        }
+    }
+
+    /// chamado a cada quadro sem o marcador: repete a última posição e aplica a política
+    pub fn marker_lost(&mut self) {
+        let since = *self.lost_since.get_or_insert_with(Instant::now);
+        self.presence = self.loss_policy.gain(since.elapsed());
+        let (x, y) = self.last_position;
+        self.apply_position(x, y);
+        self.source.set_presence(self.presence);
+    }
+
+    pub fn is_marker_lost(&self) -> bool {
+        self.lost_since.is_some()
+    }
+
+    pub fn cycle_harmony_interval(&mut self) {
+        self.set_harmony_interval(self.harmony_interval.next());
+    }
+
+    pub fn set_harmony_interval(&mut self, interval: HarmonyInterval) {
+        self.harmony_interval = interval;
+        log!("[INFO] Harmonizador: {}", self.harmony_interval.name());
+    }
+
+    pub fn harmony_interval(&self) -> HarmonyInterval {
+        self.harmony_interval
+    }
+
+    pub fn cycle_loss_policy(&mut self) {
+        self.loss_policy = self.loss_policy.next();
+        log!("[INFO] Perda do marcador: {}", self.loss_policy.name());
+    }
+
+    pub fn loss_policy(&self) -> MarkerLossPolicy {
+        self.loss_policy
+    }
+
+    fn apply_position(&mut self, x: f32, y: f32) {
+        let (frequency, amplitude) = match self.play_mode {
+            PlayMode::Theremin => self.map_position_to_audio(x, y),
+            PlayMode::DrumPads => {
+                self.update_drum_pads(x, y);
+                // a voz contínua fica em silêncio no modo pads
+                (self.last_frequency, 0.0)
+            }
+            PlayMode::Arpeggiator => self.update_arpeggiator(x, y),
+            PlayMode::Chord => self.update_chord(x, y),
+        };
+
+        let harmony = self.harmony_interval.ratio(&self.pitch_mapping, y);
+        let formant = self.formant_vowel(x);
 
         // últimos valores mesmo se o som estiver desativado
         self.last_amplitude = amplitude;
         self.last_frequency = frequency;
+        self.last_position = (x, y);
 
         // atualiza o som apenas se estiver ativado
         if self.is_enabled() {
             self.source.update_parameters(amplitude, frequency);
+            self.source.update_position(x, y);
+            self.source.update_harmony(harmony);
+            self.source.update_formant(formant);
+            // o áudio aplica a presença suavizada; o MIDI segue o mesmo ganho
+            let midi_amplitude = amplitude * self.presence;
+            self.midi_recorder
+                .record(frequency, midi_amplitude, self.pitch_mapping.reference_a4());
+            self.send_live_midi(frequency, midi_amplitude);
         }
     }
 
-    fn map_position_to_audio(x: f32, y: f32) -> (f32, f32) {
-        let amplitude = match x {
-            x if (-1.0 <= x) && (x < -0.80) => 0.10,
-            x if (-0.80 <= x) && (x < -0.60) => 0.20,
-            x if (-0.60 <= x) && (x < -0.40) => 0.30,
-            x if (-0.40 <= x) && (x < -0.20) => 0.40,
-            x if (-0.20 <= x) && (x < 0.0) => 0.50,
-            x if (0.0 <= x) && (x < 0.20) => 0.60,
-            x if (0.20 <= x) && (x < 0.40) => 0.70,
-            x if (0.40 <= x) && (x < 0.60) => 0.80,
-            x if (0.60 <= x) && (x < 0.80) => 0.90,
-            x if (0.80 <= x) && (x <= 1.0) => 1.00,
-            _ => 0.5,
-        };
+    fn send_live_midi(&mut self, frequency: f32, amplitude: f32) {
+        let reference_a4 = self.pitch_mapping.reference_a4();
+        if let Some(live) = &mut self.live_midi
+            && let Err(e) = live.update(frequency, amplitude, reference_a4)
+        {
+            eprintln!("[ERROR] Erro ao enviar MIDI, saída desativada: {}", e);
+            self.live_midi = None;
+        }
+    }
+
+    pub fn toggle_live_midi(&mut self) {
+        if let Some(mut live) = self.live_midi.take() {
+            if let Err(e) = live.release() {
+                eprintln!("[ERROR] Erro ao soltar nota MIDI: {}", e);
+            }
+            log!("[OFF] MIDI ao vivo desativado ({})", live.name());
+            return;
+        }
+
+        match open_midi_output().and_then(LiveMidi::new) {
+            Ok(live) => {
+                log!("[ON] MIDI ao vivo: {}", live.name());
+                self.live_midi = Some(live);
+            }
+            Err(e) => eprintln!("[ERROR] Erro ao abrir saída MIDI: {}", e),
+        }
+    }
 
-        let frequency = match y {
-            y if (-1.0 <= y) && (y < -0.80) => 130.81,  // C3
-            y if (-0.80 <= y) && (y < -0.60) => 146.83, // D3
-            y if (-0.60 <= y) && (y < -0.40) => 164.81, // E3
-            y if (-0.40 <= y) && (y < -0.20) => 196.00, // G3
-            y if (-0.20 <= y) && (y < 0.0) => 220.00,   // A3
-            y if (0.0 <= y) && (y < 0.20) => 261.63,    // C4
-            y if (0.20 <= y) && (y < 0.40) => 293.66,   // D4
-            y if (0.40 <= y) && (y < 0.60) => 329.63,   // E4
-            y if (0.60 <= y) && (y < 0.80) => 392.00,   // G4
-            y if (0.80 <= y) && (y <= 1.0) => 440.00,   // A4
-            _ => 440.0,
+    pub fn is_live_midi_enabled(&self) -> bool {
+        self.live_midi.is_some()
+    }
+
+    /// expressão por marcador (MPE): recebe todos os marcadores visíveis do quadro
+    pub fn update_markers(&mut self, markers: &[(i32, NormalizedPosition)]) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if let Some(mpe) = &mut self.mpe
+            && let Err(e) = mpe.update(markers, &self.pitch_mapping)
+        {
+            eprintln!("[ERROR] Erro ao enviar MPE, saída desativada: {}", e);
+            self.mpe = None;
+        }
+    }
+
+    pub fn toggle_mpe(&mut self) {
+        if let Some(mut mpe) = self.mpe.take() {
+            if let Err(e) = mpe.release_all() {
+                eprintln!("[ERROR] Erro ao soltar notas MPE: {}", e);
+            }
+            log!("[OFF] MPE desativado ({})", mpe.name());
+            return;
+        }
+
+        match open_midi_output().and_then(MpeOutput::new) {
+            Ok(mpe) => {
+                log!("[ON] MPE: {}", mpe.name());
+                self.mpe = Some(mpe);
+            }
+            Err(e) => eprintln!("[ERROR] Erro ao abrir saída MPE: {}", e),
+        }
+    }
+
+    pub fn is_mpe_enabled(&self) -> bool {
+        self.mpe.is_some()
+    }
+
+    /// notas MPE soando, `None` com o MPE desligado
+    pub fn mpe_active_notes(&self) -> Option<usize> {
+        self.mpe.as_ref().map(|mpe| mpe.active_notes())
+    }
+
+    /// vogal do filtro de formantes a partir do eixo escolhido
+    fn formant_vowel(&self, x: f32) -> Option<f32> {
+        let t = match self.formant_axis {
+            FormantAxis::Off => return None,
+            FormantAxis::X => (x + 1.0) / 2.0,
+            FormantAxis::Rotation => {
+                let range = FORMANT_ROTATION_RANGE.to_radians();
+                (self.rotation / range + 1.0) / 2.0
+            }
         };
+        Some(t.clamp(0.0, 1.0))
+    }
+
+    /// rotação do marcador em radianos, usada quando o filtro de formantes segue a rotação
+    pub fn update_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+    }
+
+    pub fn cycle_formant_axis(&mut self) {
+        self.set_formant_axis(self.formant_axis.next());
+    }
+
+    pub fn set_formant_axis(&mut self, axis: FormantAxis) {
+        self.formant_axis = axis;
+        log!("[INFO] Filtro de formantes: {}", self.formant_axis.name());
+    }
+
+    pub fn formant_axis(&self) -> FormantAxis {
+        self.formant_axis
+    }
+
+    pub fn formant_vowel_position(&self) -> Option<f32> {
+        self.formant_vowel(self.last_position.0)
+    }
+
+    fn map_position_to_audio(&mut self, x: f32, y: f32) -> (f32, f32) {
+        let amplitude = self.amplitude_mapping.map(x);
+
+        // a corda é palhetada quando a nota quantizada muda, com força dada pela velocidade
+        let note = self.pitch_mapping.note_for_position(y);
+        if self.plucked_note != Some(note) {
+            self.plucked_note = Some(note);
+            if self.source.get_voice() == VoiceType::String {
+                self.pluck_string();
+            }
+        }
+
+        let frequency = self.pitch_mapping.frequency_for_position(y);
 
         (frequency, amplitude)
     }
 
+    /// palhetada com força dada pela velocidade do marcador
+    fn pluck_string(&self) {
+        if self.is_enabled() {
+            let strength = (self.marker_speed / STRING_MAX_SPEED).clamp(STRING_MIN_PLUCK, 1.0);
+            self.source.pluck_string(strength);
+        }
+    }
+
+    fn update_drum_pads(&mut self, x: f32, y: f32) {
+        if let Some(zone) = self.drum_pads.update(x, y)
+            && self.is_enabled()
+        {
+            self.source.trigger_drum(zone);
+        }
+    }
+
+    /// Y escolhe a fundamental do arpejo, X a velocidade ou o padrão
+    fn update_arpeggiator(&mut self, x: f32, y: f32) -> (f32, f32) {
+        let root = self.pitch_mapping.note_for_position(y);
+        let notes = ARP_INTERVALS
+            .iter()
+            .map(|interval| self.pitch_mapping.note_to_frequency(root + interval))
+            .collect();
+
+        let t = ((x + 1.0) / 2.0).clamp(0.0, 1.0);
+        let (pattern, rate) = match self.arp_x_control {
+            ArpXControl::Rate => (
+                self.arp_pattern,
+                ARP_MIN_RATE + (ARP_MAX_RATE - ARP_MIN_RATE) * t,
+            ),
+            ArpXControl::Pattern => (ArpPattern::from_position(t), ARP_DEFAULT_RATE),
+        };
+
+        self.source.arpeggiator().update(notes, pattern, rate);
+
+        (self.pitch_mapping.note_to_frequency(root), ARP_AMPLITUDE)
+    }
+
+    /// Y escolhe o grau da escala, X a inversão ou a abertura do acorde
+    fn update_chord(&mut self, x: f32, y: f32) -> (f32, f32) {
+        let degree = degree_for_position(&self.pitch_mapping, y);
+        let voicing = ChordVoicing::from_position((x + 1.0) / 2.0, self.chord_type);
+        let frequencies: Vec<f32> =
+            build_chord(&self.pitch_mapping, degree, self.chord_type, voicing)
+                .iter()
+                .map(|&note| self.pitch_mapping.note_to_frequency(note))
+                .collect();
+
+        self.source.update_chord(&frequencies);
+        self.chord_degree = degree;
+        self.chord_voicing = voicing;
+
+        (frequencies[0], CHORD_AMPLITUDE)
+    }
+
+    pub fn toggle_chord_type(&mut self) {
+        self.chord_type = match self.chord_type {
+            ChordType::Triad => ChordType::Seventh,
+            ChordType::Seventh => ChordType::Triad,
+        };
+        log!("[INFO] Acorde: {}", self.chord_type.name());
+    }
+
+    pub fn chord_type(&self) -> ChordType {
+        self.chord_type
+    }
+
+    pub fn chord_degree(&self) -> usize {
+        self.chord_degree
+    }
+
+    pub fn chord_voicing(&self) -> ChordVoicing {
+        self.chord_voicing
+    }
+
+    pub fn cycle_scale(&mut self) {
+        self.set_scale(self.pitch_mapping.scale().next());
+    }
+
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.pitch_mapping.set_scale(scale);
+        log!("[INFO] Escala: {}", scale.name());
+    }
+
+    pub fn toggle_continuous_pitch(&mut self) {
+        self.set_continuous_pitch(!self.pitch_mapping.is_continuous());
+    }
+
+    pub fn set_continuous_pitch(&mut self, continuous: bool) {
+        self.pitch_mapping.set_continuous(continuous);
+
+        if continuous {
+            log!("[INFO] Altura contínua");
+        } else {
+            log!("[INFO] Altura quantizada na escala");
+        }
+    }
+
+    pub fn pitch_mapping(&self) -> &PitchMapping {
+        &self.pitch_mapping
+    }
+
+    pub fn scale(&self) -> Scale {
+        self.pitch_mapping.scale()
+    }
+
+    pub fn toggle_arp_x_control(&mut self) {
+        self.arp_x_control = match self.arp_x_control {
+            ArpXControl::Rate => ArpXControl::Pattern,
+            ArpXControl::Pattern => ArpXControl::Rate,
+        };
+        log!("[INFO] Arpejo: X controla {}", self.arp_x_control.name());
+    }
+
+    pub fn cycle_arp_pattern(&mut self) {
+        self.arp_pattern = self.arp_pattern.next();
+        log!("[INFO] Padrão do arpejo: {}", self.arp_pattern.name());
+    }
+
+    pub fn arp_x_control(&self) -> ArpXControl {
+        self.arp_x_control
+    }
+
+    pub fn arp_pattern(&self) -> ArpPattern {
+        self.source.arpeggiator().get_pattern()
+    }
+
+    pub fn arp_rate(&self) -> f32 {
+        self.source.arpeggiator().get_rate()
+    }
+
+    pub fn cycle_play_mode(&mut self) {
+        let index = PlayMode::ALL
+            .iter()
+            .position(|&mode| mode == self.play_mode)
+            .unwrap_or(0);
+        self.play_mode = PlayMode::ALL[(index + 1) % PlayMode::ALL.len()];
+        self.drum_pads.release();
+        self.source
+            .arpeggiator()
+            .set_enabled(self.play_mode == PlayMode::Arpeggiator);
+        if self.play_mode != PlayMode::Chord {
+            self.source.update_chord(&[]);
+        }
+
+        log!("[INFO] Modo: {}", self.play_mode.name());
+    }
+
+    pub fn play_mode(&self) -> PlayMode {
+        self.play_mode
+    }
+
+    pub fn drum_pads(&self) -> &DrumPads {
+        &self.drum_pads
+    }
+
+    pub fn set_sound(&mut self, enabled: bool) {
+        if enabled != self.is_enabled() {
+            self.toggle_sound();
+        }
+    }
+
     pub fn toggle_sound(&mut self) {
         let enabled = !self.is_enabled();
         self.source.set_enabled(enabled);
@@ -179,9 +1011,40 @@ impl ThereminController {
         if enabled {
             self.source
                 .update_parameters(self.last_amplitude, self.last_frequency);
-            println!("[ON] Som ativado");
+            self.source
+                .update_position(self.last_position.0, self.last_position.1);
+            log!("[ON] Som ativado");
         } else {
-            println!("[OFF] Som desativado");
+            self.midi_recorder.release();
+            self.send_live_midi(self.last_frequency, 0.0);
+            if let Some(mpe) = &mut self.mpe
+                && let Err(e) = mpe.release_all()
+            {
+                eprintln!("[ERROR] Erro ao soltar notas MPE: {}", e);
+            }
+            log!("[OFF] Som desativado");
+        }
+    }
+
+    /// grava a performance registrada até agora num .mid e começa uma nova gravação
+    pub fn export_midi(&mut self) {
+        if self.midi_recorder.is_empty() {
+            log!("[INFO] Nenhuma nota gravada para exportar");
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = format!("{}_{}.mid", MIDI_EXPORT_PREFIX, timestamp);
+
+        match self
+            .midi_recorder
+            .write(&path, self.source.clock().status().bpm)
+        {
+            Ok(()) => log!("[INFO] Performance exportada: {}", path),
+            Err(e) => eprintln!("[ERROR] Erro ao exportar MIDI: {}", e),
         }
     }
 
@@ -190,6 +1053,9 @@ impl ThereminController {
     }
 
     pub fn get_frequency(&self) -> f32 {
+        if self.play_mode == PlayMode::Arpeggiator {
+            return self.source.arpeggiator().get_current_frequency();
+        }
         self.source.get_frequency()
     }
 
@@ -197,17 +1063,209 @@ impl ThereminController {
         self.source.get_amplitude()
     }
 
+    /// passa para a próxima voz disponível
+    pub fn cycle_voice(&mut self) {
+        let current = self.source.get_voice();
+        let start = VoiceType::ALL
+            .iter()
+            .position(|&voice| voice == current)
+            .unwrap_or(0);
+
+        let next = (1..=VoiceType::ALL.len())
+            .map(|offset| VoiceType::ALL[(start + offset) % VoiceType::ALL.len()])
+            .find(|&voice| self.source.is_voice_available(voice))
+            .unwrap_or(VoiceType::Sine);
+
+        self.set_voice(next);
+    }
+
+    pub fn set_voice(&mut self, voice: VoiceType) {
+        if !self.is_voice_available(voice) {
+            log!("[INFO] Voz indisponível: {}", voice.name());
+            return;
+        }
+
+        self.source.set_voice(voice);
+        log!("[INFO] Voz: {}", voice.name());
+
+        // sem palhetada a corda ficaria muda até a próxima troca de nota
+        if voice == VoiceType::String {
+            self.pluck_string();
+        }
+    }
+
+    pub fn get_voice(&self) -> VoiceType {
+        self.source.get_voice()
+    }
+
+    pub fn is_voice_available(&self, voice: VoiceType) -> bool {
+        self.source.is_voice_available(voice)
+    }
+
+    /// alterna o eixo Y do granular entre altura e densidade
+    pub fn toggle_granular_y_control(&mut self) {
+        let next = match self.source.get_granular_y_control() {
+            Some(GranularYControl::Pitch) => GranularYControl::Density,
+            Some(GranularYControl::Density) => GranularYControl::Pitch,
+            None => {
+                log!("[INFO] Motor granular indisponível");
+                return;
+            }
+        };
+
+        self.source.set_granular_y_control(next);
+        log!("[INFO] Granular: Y controla {}", next.name());
+    }
+
+    pub fn get_granular_y_control(&self) -> Option<GranularYControl> {
+        self.source.get_granular_y_control()
+    }
+
+    /// alterna o eixo X das vozes de ruído entre mistura e cor
+    pub fn toggle_noise_x_control(&mut self) {
+        let next = match self.source.get_noise_x_control() {
+            NoiseXControl::Level => NoiseXControl::Color,
+            NoiseXControl::Color => NoiseXControl::Level,
+        };
+
+        self.source.set_noise_x_control(next);
+        log!("[INFO] Ruído: X controla {}", next.name());
+    }
+
+    pub fn get_noise_x_control(&self) -> NoiseXControl {
+        self.source.get_noise_x_control()
+    }
+
+    pub fn cycle_amplitude_curve(&mut self) {
+        self.amplitude_mapping.curve = self.amplitude_mapping.curve.next();
+        log!(
+            "[INFO] Curva de amplitude: {}",
+            self.amplitude_mapping.curve.name()
+        );
+    }
+
+    pub fn amplitude_curve(&self) -> AmplitudeCurve {
+        self.amplitude_mapping.curve
+    }
+
+    pub fn transpose_by(&mut self, semitones: i32) {
+        self.set_transpose(self.pitch_mapping.transpose().saturating_add(semitones));
+    }
+
+    pub fn set_transpose(&mut self, transpose: i32) {
+        self.pitch_mapping.set_transpose(transpose);
+        log!(
+            "[INFO] Transposição: {:+} semitons",
+            self.pitch_mapping.transpose()
+        );
+    }
+
+    pub fn looper_command(&mut self, command: LooperCommand) {
+        self.source.send_looper_command(command);
+
+        let message = match command {
+            LooperCommand::Record => "Gravar/fechar loop",
+            LooperCommand::Overdub => "Sobrepor",
+            LooperCommand::TogglePlay => "Tocar/parar",
+            LooperCommand::Clear => "Loop apagado",
+        };
+        log!("[LOOP] {}", message);
+    }
+
+    pub fn looper_status(&self) -> LooperStatus {
+        self.source.looper_status()
+    }
+
+    pub fn change_tempo(&mut self, steps: i32) {
+        let bpm = self.source.clock().status().bpm;
+        self.set_tempo(bpm + steps as f32 * CLOCK_BPM_STEP);
+    }
+
+    pub fn set_tempo(&mut self, bpm: f32) {
+        let clock = self.source.clock();
+        clock.set_bpm(bpm);
+        log!("[INFO] Tempo: {:.0} BPM", clock.status().bpm);
+    }
+
+    pub fn toggle_click(&mut self) {
+        let clock = self.source.clock();
+        let enabled = !clock.status().click_enabled;
+        clock.set_click_enabled(enabled);
+
+        if enabled {
+            log!("[ON] Metrônomo ativado");
+        } else {
+            log!("[OFF] Metrônomo desativado");
+        }
+    }
+
+    pub fn toggle_quantize(&mut self) {
+        let clock = self.source.clock();
+        let enabled = !clock.status().quantize_enabled;
+        clock.set_quantize_enabled(enabled);
+
+        if enabled {
+            log!("[ON] Quantização ao tempo ativada");
+        } else {
+            log!("[OFF] Quantização ao tempo desativada");
+        }
+    }
+
+    pub fn clock_status(&self) -> ClockStatus {
+        self.source.clock().status()
+    }
+
+    pub fn toggle_compressor(&mut self) {
+        self.set_compressor(!self.source.is_compressor_enabled());
+    }
+
+    pub fn set_compressor(&mut self, enabled: bool) {
+        self.source.set_compressor_enabled(enabled);
+
+        if enabled {
+            log!("[ON] Compressor ativado");
+        } else {
+            log!("[OFF] Compressor desativado");
+        }
+    }
+
+    pub fn is_compressor_enabled(&self) -> bool {
+        self.source.is_compressor_enabled()
+    }
+
+    pub fn meter_levels(&self) -> MeterLevels {
+        self.source.meter_levels()
+    }
+
     pub fn stop(&mut self) {
         self.sink.stop();
     }
 }
 
+/// saída MIDI configurada; sem a porta o erro volta e o MIDI continua desligado
+fn open_midi_output() -> Result<Box<dyn MidiOutput>, Box<dyn std::error::Error>> {
+    open_output(MIDI_OUTPUT_BACKEND)
+}
+
 impl Clone for ThereminSource {
     fn clone(&self) -> Self {
         Self {
             state: self.state.clone(),
             sample_rate: self.sample_rate,
-            phase: 0.0, // fase não é compartilhada
+            master: self.master.clone(),
+            active_voice: self.active_voice,
+            voices: self.voices.clone(),
+            harmony_voices: self.harmony_voices.clone(),
+            drums: self.drums.clone(),
+            arpeggiator: self.arpeggiator.clone(),
+            chord_voice: self.chord_voice.clone(),
+            formant_filter: self.formant_filter.clone(),
+            looper: self.looper.clone(),
+            clock: self.clock.clone(),
+            quantized_frequency: self.quantized_frequency,
+            presence_gain: self.presence_gain,
+            frame_sample: 0.0,
+            pending_channels: 0,
         }
     }
 }
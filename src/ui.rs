@@ -1,10 +1,20 @@
 use crate::{
-    config::{COLOR_BLUE, COLOR_GREEN, COLOR_RED, COLOR_WHITE},
-    theremin::ThereminController,
+    chord::degree_name,
+    config::{
+        CLOCK_BEATS_PER_BAR, COLOR_BLUE, COLOR_GREEN, COLOR_RED, COLOR_WHITE, COLOR_YELLOW,
+        MASTER_CEILING, TUNER_IN_TUNE_CENTS, TUNER_RANGE_CENTS,
+    },
+    formant::vowel_name,
+    harmonizer::HarmonyInterval,
+    looper::LooperMode,
+    pitch::{nearest_note, note_name},
+    theremin::{PlayMode, ThereminController, VoiceType},
 };
 use opencv::{
-    core::{Point, Point2f},
-    imgproc::{FONT_HERSHEY_SIMPLEX, LINE_AA, get_text_size, line, put_text},
+    core::{Point, Point2f, Rect},
+    imgproc::{
+        FILLED, FONT_HERSHEY_SIMPLEX, LINE_AA, circle, get_text_size, line, put_text, rectangle,
+    },
     prelude::MatTraitConst,
 };
 
@@ -34,18 +44,64 @@ impl DetectedMarker {
             center,
         }
     }
+
+    /// ângulo em radianos da borda superior (canto 0 para canto 1); 0 com o marcador em pé
+    pub fn rotation(&self) -> f32 {
+        if self.corners.len() < 2 {
+            return 0.0;
+        }
+        let dx = self.corners[1].x - self.corners[0].x;
+        let dy = self.corners[1].y - self.corners[0].y;
+        dy.atan2(dx)
+    }
+
+    /// comprimento médio dos lados, em pixels
+    pub fn side_length(&self) -> f32 {
+        let count = self.corners.len();
+        if count < 2 {
+            return 0.0;
+        }
+        let perimeter: f32 = (0..count)
+            .map(|i| {
+                let j = (i + 1) % count;
+                let dx = self.corners[i].x - self.corners[j].x;
+                let dy = self.corners[i].y - self.corners[j].y;
+                (dx * dx + dy * dy).sqrt()
+            })
+            .sum();
+        perimeter / count as f32
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct NormalizedPosition {
     pub x: f32,
     pub y: f32,
+    pub rotation: f32,
+    /// lado do marcador como fração da altura do quadro
+    pub size: f32,
     pub detected: bool,
 }
 
 impl NormalizedPosition {
     pub fn new(x: f32, y: f32, detected: bool) -> Self {
-        NormalizedPosition { x, y, detected }
+        NormalizedPosition {
+            x,
+            y,
+            rotation: 0.0,
+            size: 0.0,
+            detected,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
     }
 }
 
@@ -79,8 +135,96 @@ pub fn draw_theremin_info(
         false,
     )?;
 
+    let voice_text = match (controller.get_voice(), controller.get_granular_y_control()) {
+        (VoiceType::Granular, Some(y_control)) => {
+            format!("{} (Y: {})", VoiceType::Granular.name(), y_control.name())
+        }
+        (VoiceType::Noise(color), _) => format!(
+            "{} (X: {})",
+            VoiceType::Noise(color).name(),
+            controller.get_noise_x_control().name()
+        ),
+        (voice, _) => voice.name().to_string(),
+    };
+
+    let mode_text = match controller.play_mode() {
+        PlayMode::Arpeggiator => format!(
+            "Modo: {} | {} | {:.1} notas/s (X: {})",
+            PlayMode::Arpeggiator.name(),
+            controller.arp_pattern().name(),
+            controller.arp_rate(),
+            controller.arp_x_control().name()
+        ),
+        PlayMode::Chord => format!(
+            "Modo: {} | {} ({}, {}) | {}",
+            PlayMode::Chord.name(),
+            degree_name(controller.pitch_mapping(), controller.chord_degree()),
+            controller.chord_type().name(),
+            controller.chord_voicing().name(),
+            controller.scale().name()
+        ),
+        mode => format!("Modo: {} | {}", mode.name(), controller.scale().name()),
+    };
+
+    let loss_text = format!("Perda do marcador: {}", controller.loss_policy().name());
+    let loss_color = if controller.is_marker_lost() {
+        COLOR_YELLOW
+    } else {
+        COLOR_WHITE
+    };
+
+    // acima do looper, longe da linha do overlay de debug
+    put_text(
+        frame,
+        &loss_text,
+        Point::new(10, frame_height - 100),
+        FONT_HERSHEY_SIMPLEX,
+        0.5,
+        loss_color,
+        1,
+        LINE_AA,
+        false,
+    )?;
+
+    // uma linha por estado, empilhadas acima da perda do marcador
+    let mut status_lines = vec![mode_text, format!("Voz: {}", voice_text)];
+    if let Some(vowel) = controller.formant_vowel_position() {
+        status_lines.push(format!(
+            "Vogal: {} ({})",
+            vowel_name(vowel),
+            controller.formant_axis().name()
+        ));
+    }
+    if controller.harmony_interval() != HarmonyInterval::Off {
+        status_lines.push(format!("Harm: {}", controller.harmony_interval().name()));
+    }
+    if controller.is_live_midi_enabled() {
+        status_lines.push(String::from("MIDI ao vivo"));
+    }
+    if let Some(notes) = controller.mpe_active_notes() {
+        status_lines.push(format!("MPE: {} notas", notes));
+    }
+
+    for (row, status_text) in status_lines.iter().rev().enumerate() {
+        put_text(
+            frame,
+            status_text,
+            Point::new(10, frame_height - 125 - row as i32 * 20),
+            FONT_HERSHEY_SIMPLEX,
+            0.5,
+            COLOR_WHITE,
+            1,
+            LINE_AA,
+            false,
+        )?;
+    }
+
     let freq_text = format!("Freq: {:.1} Hz", controller.get_frequency());
-    let amp_text = format!("Amp: {:.2}", controller.get_amplitude());
+    let amp_text = format!(
+        "Amp: {:.2} ({})",
+        controller.get_amplitude(),
+        controller.amplitude_curve().name()
+    );
 
     let bottom_y = frame_height - 20;
 
@@ -113,6 +257,313 @@ pub fn draw_theremin_info(
     Ok(())
 }
 
+/// afinador: nota mais próxima, desvio em cents e agulha
+pub fn draw_tuner(
+    frame: &mut opencv::core::Mat,
+    controller: &ThereminController,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let frame_width = frame.cols();
+    let reference_a4 = controller.pitch_mapping().reference_a4();
+    let (note, cents) = nearest_note(controller.get_frequency(), reference_a4);
+
+    let color = if cents.abs() <= TUNER_IN_TUNE_CENTS {
+        COLOR_GREEN
+    } else if cents.abs() <= TUNER_RANGE_CENTS / 2.0 {
+        COLOR_YELLOW
+    } else {
+        COLOR_RED
+    };
+
+    let scale_width = 200;
+    let scale_x = (frame_width - scale_width) / 2;
+    let scale_y = 130;
+    let center_x = scale_x + scale_width / 2;
+
+    let tuner_text = format!("{} {:+.0} c", note_name(note), cents);
+    let text_size = get_text_size(&tuner_text, FONT_HERSHEY_SIMPLEX, 0.7, 2, &mut 0)?;
+
+    put_text(
+        frame,
+        &tuner_text,
+        Point::new(center_x - text_size.width / 2, scale_y - 15),
+        FONT_HERSHEY_SIMPLEX,
+        0.7,
+        color,
+        2,
+        LINE_AA,
+        false,
+    )?;
+
+    line(
+        frame,
+        Point::new(scale_x, scale_y),
+        Point::new(scale_x + scale_width, scale_y),
+        COLOR_WHITE,
+        1,
+        LINE_AA,
+        0,
+    )?;
+
+    // marca central e marcas da faixa afinada
+    let in_tune_offset =
+        (TUNER_IN_TUNE_CENTS / TUNER_RANGE_CENTS * (scale_width / 2) as f32) as i32;
+    for (x, half_height) in [
+        (center_x, 8),
+        (center_x - in_tune_offset, 4),
+        (center_x + in_tune_offset, 4),
+    ] {
+        line(
+            frame,
+            Point::new(x, scale_y - half_height),
+            Point::new(x, scale_y + half_height),
+            COLOR_WHITE,
+            1,
+            LINE_AA,
+            0,
+        )?;
+    }
+
+    let needle_offset = (cents / TUNER_RANGE_CENTS).clamp(-1.0, 1.0) * (scale_width / 2) as f32;
+    let needle_x = center_x + needle_offset as i32;
+
+    line(
+        frame,
+        Point::new(needle_x, scale_y - 10),
+        Point::new(needle_x, scale_y + 10),
+        color,
+        3,
+        LINE_AA,
+        0,
+    )?;
+
+    Ok(())
+}
+
+/// indicador de tempo: pisca a cada batida, mais forte no primeiro tempo do compasso
+pub fn draw_beat_indicator(
+    frame: &mut opencv::core::Mat,
+    controller: &ThereminController,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let status = controller.clock_status();
+    let frame_width = frame.cols();
+
+    let beat_in_bar = status.beat % CLOCK_BEATS_PER_BAR as u64;
+    let flash = status.beat_phase < 0.15;
+    let color = if !flash {
+        COLOR_WHITE
+    } else if beat_in_bar == 0 {
+        COLOR_RED
+    } else {
+        COLOR_YELLOW
+    };
+
+    let mut flags = String::new();
+    if status.click_enabled {
+        flags.push_str(" CLICK");
+    }
+    if status.quantize_enabled {
+        flags.push_str(" Q");
+    }
+
+    let tempo_text = format!(
+        "{:.0} BPM {}/{}{}",
+        status.bpm,
+        beat_in_bar + 1,
+        CLOCK_BEATS_PER_BAR,
+        flags
+    );
+    let text_size = get_text_size(&tempo_text, FONT_HERSHEY_SIMPLEX, 0.6, 1, &mut 0)?;
+    let text_x = frame_width - text_size.width - 10;
+
+    put_text(
+        frame,
+        &tempo_text,
+        Point::new(text_x, 90),
+        FONT_HERSHEY_SIMPLEX,
+        0.6,
+        color,
+        1,
+        LINE_AA,
+        false,
+    )?;
+
+    circle(
+        frame,
+        Point::new(text_x - 15, 85),
+        8,
+        color,
+        if flash { FILLED } else { 1 },
+        LINE_AA,
+        0,
+    )?;
+
+    Ok(())
+}
+
+/// estado do looper com tempo gravado ou posição no loop
+pub fn draw_looper_status(
+    frame: &mut opencv::core::Mat,
+    controller: &ThereminController,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let status = controller.looper_status();
+
+    let (total, color) = match status.mode {
+        LooperMode::Empty => return Ok(()),
+        LooperMode::Recording => (status.max_length, COLOR_RED),
+        LooperMode::Overdubbing => (status.length, COLOR_YELLOW),
+        _ => (status.length, COLOR_GREEN),
+    };
+
+    let text = format!(
+        "Loop: {} {:.1}/{:.1}s",
+        status.mode.name(),
+        status.position,
+        total
+    );
+
+    put_text(
+        frame,
+        &text,
+        Point::new(10, frame.rows() - 75),
+        FONT_HERSHEY_SIMPLEX,
+        0.6,
+        color,
+        1,
+        LINE_AA,
+        false,
+    )?;
+
+    Ok(())
+}
+
+/// barra de nível do master (RMS preenchido, pico como linha)
+pub fn draw_master_meter(
+    frame: &mut opencv::core::Mat,
+    controller: &ThereminController,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let levels = controller.meter_levels();
+    let frame_height = frame.rows();
+
+    let meter_width = 200;
+    let meter_height = 10;
+    let meter_x = 10;
+    let meter_y = frame_height - 55;
+
+    let rms_width = ((levels.rms / MASTER_CEILING).clamp(0.0, 1.0) * meter_width as f32) as i32;
+    let peak_x =
+        meter_x + ((levels.peak / MASTER_CEILING).clamp(0.0, 1.0) * meter_width as f32) as i32;
+
+    let level_color = if levels.gain_reduction < 0.99 {
+        COLOR_RED
+    } else if levels.peak > MASTER_CEILING * 0.7 {
+        COLOR_YELLOW
+    } else {
+        COLOR_GREEN
+    };
+
+    rectangle(
+        frame,
+        Rect::new(meter_x, meter_y, rms_width, meter_height),
+        level_color,
+        FILLED,
+        LINE_AA,
+        0,
+    )?;
+
+    rectangle(
+        frame,
+        Rect::new(meter_x, meter_y, meter_width, meter_height),
+        COLOR_WHITE,
+        1,
+        LINE_AA,
+        0,
+    )?;
+
+    line(
+        frame,
+        Point::new(peak_x, meter_y - 2),
+        Point::new(peak_x, meter_y + meter_height + 2),
+        COLOR_WHITE,
+        2,
+        LINE_AA,
+        0,
+    )?;
+
+    let mut status_text = String::new();
+    if levels.gain_reduction < 0.99 {
+        status_text.push_str("LIM ");
+    }
+    if controller.is_compressor_enabled() {
+        status_text.push_str("COMP");
+    }
+
+    if !status_text.is_empty() {
+        put_text(
+            frame,
+            &status_text,
+            Point::new(meter_x + meter_width + 10, meter_y + meter_height),
+            FONT_HERSHEY_SIMPLEX,
+            0.5,
+            level_color,
+            1,
+            LINE_AA,
+            false,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// desenha as zonas do modo pads, destacando a zona ativa
+pub fn draw_drum_zones(
+    frame: &mut opencv::core::Mat,
+    controller: &ThereminController,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if controller.play_mode() != PlayMode::DrumPads {
+        return Ok(());
+    }
+
+    let frame_width = frame.cols() as f32;
+    let frame_height = frame.rows() as f32;
+    let drum_pads = controller.drum_pads();
+
+    for (index, zone) in drum_pads.zones().iter().enumerate() {
+        let left = ((zone.left + 1.0) / 2.0 * frame_width) as i32;
+        let top = ((zone.top + 1.0) / 2.0 * frame_height) as i32;
+        let right = ((zone.right + 1.0) / 2.0 * frame_width) as i32;
+        let bottom = ((zone.bottom + 1.0) / 2.0 * frame_height) as i32;
+
+        let (color, thickness) = if drum_pads.active_zone() == Some(index) {
+            (COLOR_YELLOW, 3)
+        } else {
+            (COLOR_BLUE, 1)
+        };
+
+        rectangle(
+            frame,
+            Rect::new(left, top, right - left, bottom - top),
+            color,
+            thickness,
+            LINE_AA,
+            0,
+        )?;
+
+        put_text(
+            frame,
+            &zone.name,
+            Point::new(left + 8, bottom - 10),
+            FONT_HERSHEY_SIMPLEX,
+            0.6,
+            color,
+            thickness.min(2),
+            LINE_AA,
+            false,
+        )?;
+    }
+
+    Ok(())
+}
+
 pub fn draw_markers(
     frame: &mut opencv::core::Mat,
     markers: &[DetectedMarker],
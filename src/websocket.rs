@@ -0,0 +1,508 @@
+use std::error::Error;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+
+use crate::{
+    command::Command,
+    config::{WEBSOCKET_ALLOWED_ORIGINS, WEBSOCKET_MAX_BUFFER, WEBSOCKET_MAX_CLIENTS},
+    osc::text_command,
+};
+
+/// constante do RFC 6455 usada no aceite do handshake
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClientState {
+    /// esperando o pedido HTTP de upgrade
+    Handshake,
+    Open,
+    Closed,
+}
+
+struct Client {
+    stream: TcpStream,
+    address: SocketAddr,
+    state: ClientState,
+    input: Vec<u8>,
+    /// bytes ainda não aceitos pelo socket (escrita não bloqueante)
+    output: Vec<u8>,
+}
+
+impl Client {
+    /// lê o que chegou e devolve os comandos de texto recebidos
+    fn receive(&mut self) -> Vec<Command> {
+        let mut buffer = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.state = ClientState::Closed;
+                    break;
+                }
+                Ok(size) => self.input.extend_from_slice(&buffer[..size]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => {
+                    self.state = ClientState::Closed;
+                    break;
+                }
+            }
+        }
+
+        if self.input.len() > WEBSOCKET_MAX_BUFFER {
+            self.state = ClientState::Closed;
+        }
+
+        if self.state == ClientState::Handshake {
+            self.handshake();
+        }
+
+        let mut commands = Vec::new();
+        while self.state == ClientState::Open {
+            let Some((opcode, payload, consumed)) = parse_frame(&self.input) else {
+                break;
+            };
+            self.input.drain(..consumed);
+
+            match opcode {
+                OPCODE_TEXT => {
+                    let text = String::from_utf8_lossy(&payload);
+                    match text_command(&text) {
+                        Some(command) => commands.push(command),
+                        None => log!("[INFO] Comando WebSocket ignorado: {}", text),
+                    }
+                }
+                OPCODE_CLOSE => {
+                    queue_frame(&mut self.output, OPCODE_CLOSE, &payload);
+                    self.flush();
+                    self.state = ClientState::Closed;
+                }
+                OPCODE_PING => queue_frame(&mut self.output, OPCODE_PONG, &payload),
+                _ => {}
+            }
+        }
+        commands
+    }
+
+    fn handshake(&mut self) {
+        let Some(end) = self.input.windows(4).position(|w| w == b"\r\n\r\n") else {
+            return;
+        };
+
+        let request = String::from_utf8_lossy(&self.input[..end]).to_string();
+        self.input.drain(..end + 4);
+
+        // o navegador deixa qualquer página abrir um WebSocket para localhost;
+        // só o Origin separa o painel local de um site qualquer
+        if let Some(origin) = header(&request, "Origin")
+            && !origin_allowed(origin)
+        {
+            log!(
+                "[INFO] Cliente WebSocket recusado (origem {}): {}",
+                origin, self.address
+            );
+            self.reject("403 Forbidden");
+            return;
+        }
+
+        let Some(key) = header(&request, "Sec-WebSocket-Key") else {
+            self.reject("400 Bad Request");
+            return;
+        };
+
+        let accept = accept_key(key);
+        let response = format!(
+            concat!(
+                "HTTP/1.1 101 Switching Protocols\r\n",
+                "Upgrade: websocket\r\n",
+                "Connection: Upgrade\r\n",
+                "Sec-WebSocket-Accept: {}\r\n\r\n"
+            ),
+            accept
+        );
+        self.output.extend_from_slice(response.as_bytes());
+        self.state = ClientState::Open;
+        log!("[INFO] Cliente WebSocket conectado: {}", self.address);
+    }
+
+    fn reject(&mut self, status: &str) {
+        let response = format!("HTTP/1.1 {}\r\nConnection: close\r\n\r\n", status);
+        self.output.extend_from_slice(response.as_bytes());
+        self.flush();
+        self.state = ClientState::Closed;
+    }
+
+    fn flush(&mut self) {
+        while !self.output.is_empty() {
+            match self.stream.write(&self.output) {
+                Ok(0) => {
+                    self.state = ClientState::Closed;
+                    return;
+                }
+                Ok(size) => {
+                    self.output.drain(..size);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => {
+                    self.state = ClientState::Closed;
+                    return;
+                }
+            }
+        }
+
+        // cliente lento demais: melhor derrubar do que acumular quadros velhos
+        if self.output.len() > WEBSOCKET_MAX_BUFFER {
+            self.state = ClientState::Closed;
+        }
+    }
+}
+
+/// servidor WebSocket local: transmite o estado de cada quadro e aceita comandos de texto
+/// (os mesmos endereços do OSC, ex. "/theremin/sound" ou "/theremin/scale Maior")
+pub struct WebSocketServer {
+    listener: TcpListener,
+    address: SocketAddr,
+    clients: Vec<Client>,
+}
+
+impl WebSocketServer {
+    pub fn bind(address: &str) -> Result<Self, Box<dyn Error>> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        Ok(WebSocketServer {
+            listener,
+            address,
+            clients: Vec::new(),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// aceita conexões novas e devolve os comandos recebidos desde a última chamada
+    pub fn poll(&mut self) -> Vec<Command> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => self.add_client(stream, address),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("[ERROR] Erro ao aceitar cliente WebSocket: {}", e);
+                    break;
+                }
+            }
+        }
+
+        let mut commands = Vec::new();
+        for client in &mut self.clients {
+            commands.extend(client.receive());
+            client.flush();
+        }
+        self.remove_closed();
+        commands
+    }
+
+    /// manda o texto para todos os clientes já conectados
+    pub fn broadcast(&mut self, text: &str) {
+        for client in &mut self.clients {
+            if client.state == ClientState::Open {
+                queue_frame(&mut client.output, OPCODE_TEXT, text.as_bytes());
+                client.flush();
+            }
+        }
+        self.remove_closed();
+    }
+
+    fn add_client(&mut self, stream: TcpStream, address: SocketAddr) {
+        if self.clients.len() >= WEBSOCKET_MAX_CLIENTS {
+            log!("[INFO] Cliente WebSocket recusado (limite): {}", address);
+            return;
+        }
+
+        if let Err(e) = stream.set_nonblocking(true) {
+            eprintln!("[ERROR] Erro ao configurar cliente WebSocket: {}", e);
+            return;
+        }
+        // quadros pequenos a cada frame: sem esperar o Nagle juntar pacotes
+        let _ = stream.set_nodelay(true);
+
+        self.clients.push(Client {
+            stream,
+            address,
+            state: ClientState::Handshake,
+            input: Vec::new(),
+            output: Vec::new(),
+        });
+    }
+
+    fn remove_closed(&mut self) {
+        self.clients.retain(|client| {
+            if client.state == ClientState::Closed {
+                log!("[INFO] Cliente WebSocket desconectado: {}", client.address);
+                return false;
+            }
+            true
+        });
+    }
+}
+
+/// valor do cabeçalho HTTP, sem diferenciar maiúsculas no nome
+fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request.lines().find_map(|line| {
+        let (header, value) = line.split_once(':')?;
+        header
+            .trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim())
+    })
+}
+
+/// origem da lista WEBSOCKET_ALLOWED_ORIGINS, em qualquer porta
+fn origin_allowed(origin: &str) -> bool {
+    WEBSOCKET_ALLOWED_ORIGINS.iter().any(|allowed| {
+        origin
+            .strip_prefix(allowed)
+            .is_some_and(|port| port.is_empty() || port.starts_with(':'))
+    })
+}
+
+/// valor de Sec-WebSocket-Accept para a chave do cliente
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key, HANDSHAKE_GUID).as_bytes()))
+}
+
+/// quadro do servidor: sempre final e sem máscara
+fn queue_frame(output: &mut Vec<u8>, opcode: u8, payload: &[u8]) {
+    output.push(0x80 | opcode);
+    match payload.len() {
+        len if len < 126 => output.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            output.push(126);
+            output.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            output.push(127);
+            output.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    output.extend_from_slice(payload);
+}
+
+/// (opcode, payload sem máscara, bytes consumidos) do primeiro quadro completo do buffer
+fn parse_frame(buffer: &[u8]) -> Option<(u8, Vec<u8>, usize)> {
+    let opcode = buffer.first()? & 0x0F;
+    let second = *buffer.get(1)?;
+    let masked = second & 0x80 != 0;
+
+    let (length, mut offset) = match second & 0x7F {
+        126 => (
+            u16::from_be_bytes(buffer.get(2..4)?.try_into().ok()?) as usize,
+            4,
+        ),
+        127 => (
+            u64::from_be_bytes(buffer.get(2..10)?.try_into().ok()?) as usize,
+            10,
+        ),
+        length => (length as usize, 2),
+    };
+
+    let mask = if masked {
+        let mask: [u8; 4] = buffer.get(offset..offset + 4)?.try_into().ok()?;
+        offset += 4;
+        Some(mask)
+    } else {
+        None
+    };
+
+    let mut payload = buffer.get(offset..offset.checked_add(length)?)?.to_vec();
+    if let Some(mask) = mask {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+
+    Some((opcode, payload, offset + length))
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(h) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn sha1_known_digests() {
+        let hex = |digest: [u8; 20]| -> String {
+            digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+        };
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        // mais de um bloco de 64 bytes
+        assert_eq!(
+            hex(sha1(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+    }
+
+    #[test]
+    fn base64_pads_partial_groups() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn parse_frame_unmasks_client_text() {
+        // exemplo do RFC 6455: "Hello" mascarado
+        let frame = [
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+        ];
+        assert_eq!(
+            parse_frame(&frame),
+            Some((OPCODE_TEXT, b"Hello".to_vec(), frame.len()))
+        );
+    }
+
+    #[test]
+    fn parse_frame_waits_for_the_whole_frame() {
+        let frame = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f];
+        assert_eq!(parse_frame(&frame), None);
+        assert_eq!(parse_frame(&[0x81]), None);
+        // tamanho de 64 bits absurdo não estoura a soma
+        let mut huge = vec![0x82, 0x7f];
+        huge.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert_eq!(parse_frame(&huge), None);
+    }
+
+    #[test]
+    fn queued_frames_parse_back() {
+        for len in [0, 125, 126, 70_000] {
+            let payload = vec![b'x'; len];
+            let mut output = Vec::new();
+            queue_frame(&mut output, OPCODE_TEXT, &payload);
+            assert_eq!(output[0], 0x80 | OPCODE_TEXT);
+            assert_eq!(
+                parse_frame(&output),
+                Some((OPCODE_TEXT, payload, output.len()))
+            );
+        }
+    }
+
+    #[test]
+    fn queue_frame_uses_extended_lengths() {
+        let mut output = Vec::new();
+        queue_frame(&mut output, OPCODE_PONG, &[0; 200]);
+        assert_eq!(&output[..4], &[0x80 | OPCODE_PONG, 126, 0, 200]);
+    }
+
+    #[test]
+    fn only_local_origins_are_allowed() {
+        assert!(origin_allowed("http://localhost"));
+        assert!(origin_allowed("http://localhost:3000"));
+        assert!(origin_allowed("http://127.0.0.1:8080"));
+        assert!(!origin_allowed("http://localhost.example.com"));
+        assert!(!origin_allowed("https://example.com"));
+        assert!(!origin_allowed("null"));
+    }
+
+    #[test]
+    fn header_ignores_name_case() {
+        let request = "GET / HTTP/1.1\r\nsec-websocket-key: abc==\r\nOrigin: http://localhost";
+        assert_eq!(header(request, "Sec-WebSocket-Key"), Some("abc=="));
+        assert_eq!(header(request, "Origin"), Some("http://localhost"));
+        assert_eq!(header(request, "Host"), None);
+    }
+}